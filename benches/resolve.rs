@@ -0,0 +1,249 @@
+//! Benchmarks for resolving, refreshing and parsing templates
+//!
+//! Run with `cargo bench --all-features`
+//!
+//! `resolve_hot_loop/file_recently_changed` covers the window after a file was
+//! changed, where `FileStore` compares the file's content because the
+//! modification time could be too coarse to notice another edit. Only using the
+//! 2 second window of coarse filesystems when the modification time has no
+//! sub-second part (and a 50ms window otherwise) means an unchanged file is only
+//! `stat`ed, without reading (and allocating) its content:
+//!
+//! | benchmark                               | before   | after    |
+//! |-----------------------------------------|----------|----------|
+//! | resolve_hot_loop/memory                 | 41.2 ns  | 41.7 ns  |
+//! | resolve_hot_loop/file                   | 884 ns   | 581 ns   |
+//! | resolve_hot_loop/file_recently_changed  | 24.36 µs | 1.00 µs  |
+//!
+//! `render/static` covers templates without any `${..}`, which skip the markings
+//! parser entirely. The interpolated case only gains a scan for `${`, so the
+//! difference there is run-to-run noise:
+//!
+//! | benchmark           | before | after  |
+//! |---------------------|--------|--------|
+//! | render/static       | 103 ns | 73 ns  |
+//! | render/interpolated | 382 ns | 426 ns |
+//!
+//! `apply/pooled` renders static templates on 4 threads into the buffers of a
+//! shared `RenderPool`. Counting with a global allocator, 4000 renders went from
+//! 4000 allocations to none once the pool was warm. Rendering straight into the
+//! pooled buffer with `apply_fmt` (rather than copying the output of `apply`
+//! into it) brought the long template in line with allocating. The system
+//! allocator's thread caches are still faster than the pool's lock for short
+//! outputs, so the pool is for reducing allocator pressure (e.g. with a
+//! contended global allocator), not for speed on its own:
+//!
+//! | benchmark              | allocated | pooled (copied) | pooled (apply_fmt) |
+//! |------------------------|-----------|-----------------|--------------------|
+//! | apply/{short}          | 149 µs    | 293 µs          | 270 µs             |
+//! | apply/{long} (5000 B)  | 435 µs    | 1.33 ms         | 402 µs             |
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use std::time::{Duration, SystemTime};
+use template::{
+    markings, FileStore, LoadFunction, MemoryStore, NameCasing, RenderPool, Resolver, Template,
+    TemplateStore, Templates,
+};
+
+const NAMESPACES: usize = 100;
+const VARIANTS: usize = 10;
+
+fn keys() -> impl Iterator<Item = (String, String)> {
+    (0..NAMESPACES).flat_map(|ns| {
+        (0..VARIANTS).map(move |var| (format!("namespace_{}", ns), format!("variant_{}", var)))
+    })
+}
+
+fn json() -> String {
+    let namespaces = (0..NAMESPACES)
+        .map(|ns| {
+            let variants = (0..VARIANTS)
+                .map(|var| format!(r#""variant_{}": "hello ${{name}} from {}""#, var, ns))
+                .collect::<Vec<_>>()
+                .join(",");
+            format!(r#""namespace_{}": {{{}}}"#, ns, variants)
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{{{}}}", namespaces)
+}
+
+fn toml() -> String {
+    let mut out = String::new();
+    for ns in 0..NAMESPACES {
+        out.push_str(&format!("[namespace_{}]\n", ns));
+        for var in 0..VARIANTS {
+            out.push_str(&format!(
+                "variant_{} = 'hello ${{name}} from {}'\n",
+                var, ns
+            ));
+        }
+    }
+    out
+}
+
+fn yaml() -> String {
+    let mut out = String::new();
+    for ns in 0..NAMESPACES {
+        out.push_str(&format!("namespace_{}:\n", ns));
+        for var in 0..VARIANTS {
+            out.push_str(&format!("  variant_{}: hello ${{name}} from {}\n", var, ns));
+        }
+    }
+    out
+}
+
+fn lines() -> String {
+    keys()
+        .map(|(ns, var)| format!("{}.{} = hello ${{name}}\n", ns, var))
+        .collect()
+}
+
+fn formats() -> Vec<(&'static str, String, LoadFunction)> {
+    vec![
+        ("json", json(), template::load_json),
+        ("toml", toml(), template::load_toml),
+        ("yaml", yaml(), template::load_yaml),
+        ("lines", lines(), template::load_lines),
+    ]
+}
+
+fn resolve_hot_loop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resolve_hot_loop");
+
+    let mut resolver = Resolver::new(MemoryStore::new(toml(), template::load_toml)).unwrap();
+    group.bench_function("memory", |b| {
+        b.iter(|| black_box(resolver.resolve("namespace_42", "variant_7").is_some()))
+    });
+
+    let file = std::env::temp_dir().join("template_bench_resolve.toml");
+    std::fs::write(&file, toml()).unwrap();
+    let store = FileStore::new(file.clone(), template::load_toml).unwrap();
+    let mut resolver = Resolver::new(store).unwrap();
+    group.bench_function("file", |b| {
+        b.iter(|| black_box(resolver.resolve("namespace_42", "variant_7").is_some()))
+    });
+
+    // the file was modified recently, but not in the same clock tick it was loaded in
+    let recent = std::env::temp_dir().join("template_bench_recent.toml");
+    group.bench_function("file_recently_changed", |b| {
+        b.iter_batched_ref(
+            || {
+                std::fs::write(&recent, toml()).unwrap();
+                let modified = SystemTime::now() - Duration::from_millis(500);
+                let fd = std::fs::OpenOptions::new().write(true).open(&recent);
+                fd.unwrap().set_modified(modified).unwrap();
+                let store = FileStore::new(recent.clone(), template::load_toml).unwrap();
+                Resolver::new(store).unwrap()
+            },
+            |resolver| black_box(resolver.resolve("namespace_42", "variant_7").is_some()),
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.finish();
+    let _ = std::fs::remove_file(file);
+    let _ = std::fs::remove_file(recent);
+}
+
+fn refresh_on_change(c: &mut Criterion) {
+    let mut group = c.benchmark_group("refresh_on_change");
+    for (name, data, loader) in formats() {
+        let mut templates = Templates::new(MemoryStore::new(data.clone(), loader)).unwrap();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                templates.store_mut().update(data.as_str());
+                templates.refresh().unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+fn cold_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cold_parse");
+    for (name, data, loader) in formats() {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || MemoryStore::new(data.as_str(), loader),
+                |mut store| black_box(store.parse_map().unwrap()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    let data = "static.okay = okay response\ndynamic.hello = hello ${name}!\n";
+    let mut resolver = Resolver::new(MemoryStore::new(data, template::load_lines)).unwrap();
+    group.bench_function("static", |b| {
+        b.iter(|| black_box(resolver.render("static", "okay", markings::Args::new())))
+    });
+    group.bench_function("interpolated", |b| {
+        let args = || markings::Args::new().with("name", "world");
+        b.iter(|| black_box(resolver.render("dynamic", "hello", args())))
+    });
+    group.finish();
+}
+
+fn apply(c: &mut Criterion) {
+    struct Okay;
+    impl Template for Okay {
+        fn namespace(_: NameCasing) -> &'static str {
+            "static"
+        }
+        fn name(_: NameCasing) -> &'static str {
+            "okay"
+        }
+        fn variant(&self, _: NameCasing) -> &'static str {
+            "okay"
+        }
+        fn apply(&self, input: &str) -> Option<String> {
+            Some(input.to_string())
+        }
+        fn apply_fmt(
+            &self,
+            input: &str,
+            out: &mut dyn std::fmt::Write,
+        ) -> Result<bool, std::fmt::Error> {
+            out.write_str(input).map(|_| true)
+        }
+    }
+
+    const THREADS: usize = 4;
+    const RENDERS: usize = 1000;
+
+    let mut group = c.benchmark_group("apply");
+    let pool = RenderPool::new(THREADS);
+    for (name, input) in [
+        ("short", "okay response".to_string()),
+        ("long", "okay ".repeat(1000)),
+    ] {
+        group.bench_function(format!("allocated/{}", name), |b| {
+            b.iter(|| concurrently(|| drop(black_box(Okay.apply(&input)))))
+        });
+        group.bench_function(format!("pooled/{}", name), |b| {
+            b.iter(|| concurrently(|| drop(black_box(Okay.apply_pooled(&input, &pool)))))
+        });
+    }
+    group.finish();
+
+    fn concurrently(f: impl Fn() + Sync) {
+        std::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|| (0..RENDERS).for_each(|_| f()));
+            }
+        })
+    }
+}
+
+criterion_group!(
+    benches,
+    resolve_hot_loop,
+    refresh_on_change,
+    cold_parse,
+    render,
+    apply
+);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+//! A hand-written `LoadFunction` for a bespoke line-based format
+//!
+//! This builds without any of the format features:
+//! `cargo run --example line_loader --no-default-features`
+//!
+//! Each line looks like `namespace.variant: template`
+use template::{Error, MemoryStore, Resolver, TemplateMap};
+
+const TEMPLATES: &str = "
+response.hello: hello ${name}!
+response.count_items: count is: ${count}
+response.okay: okay response
+";
+
+fn load_custom(input: &str) -> Result<TemplateMap<String>, Error> {
+    let mut map = std::collections::HashMap::<_, std::collections::HashMap<_, _>>::new();
+    for line in input.lines().map(str::trim).filter(|s| !s.is_empty()) {
+        let (key, template) = line
+            .split_once(':')
+            .ok_or_else(|| Error::Deserialize(format!("missing ':' in {:?}", line).into()))?;
+        let (namespace, variant) = key
+            .split_once('.')
+            .ok_or_else(|| Error::Deserialize(format!("missing '.' in {:?}", key).into()))?;
+        map.entry(namespace.to_string())
+            .or_default()
+            .insert(variant.to_string(), template.trim().to_string());
+    }
+    Ok(map.into_iter().map(|(k, v)| (k, v.into())).collect())
+}
+
+fn main() -> Result<(), Error> {
+    let mut resolver = Resolver::new(MemoryStore::new(TEMPLATES, load_custom))?;
+    for variant in &["hello", "count_items", "okay"] {
+        println!("{}: {:?}", variant, resolver.resolve("response", variant));
+    }
+    Ok(())
+}
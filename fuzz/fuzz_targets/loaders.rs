@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+const MAX_BYTES: usize = 64 * 1024;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = template::load_json_limited(input, MAX_BYTES);
+        let _ = template::load_toml_limited(input, MAX_BYTES);
+        let _ = template::load_yaml_limited(input, MAX_BYTES);
+    }
+});
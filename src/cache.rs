@@ -0,0 +1,47 @@
+use std::num::NonZeroUsize;
+
+// (namespace, variant, the args sorted by key)
+type Key = (String, String, Vec<(String, String)>);
+
+/// An LRU cache of rendered templates, see `Resolver::with_render_cache`
+///
+/// The entries are only valid for the version of the templates they were rendered from
+#[derive(Debug)]
+pub(crate) struct RenderCache {
+    entries: lru::LruCache<Key, String>,
+    version: u64,
+}
+
+impl RenderCache {
+    pub(crate) fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: lru::LruCache::new(capacity),
+            version: 0,
+        }
+    }
+
+    pub(crate) fn key(namespace: &str, variant: &str, args: &markings::Args<'_>) -> Key {
+        let mut args = args
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<Vec<_>>();
+        args.sort();
+        (namespace.to_string(), variant.to_string(), args)
+    }
+
+    pub(crate) fn get(&mut self, key: &Key, version: u64) -> Option<&String> {
+        if self.version != version {
+            self.entries.clear();
+            self.version = version;
+        }
+        self.entries.get(key)
+    }
+
+    pub(crate) fn insert(&mut self, key: Key, output: String) {
+        self.entries.put(key, output);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
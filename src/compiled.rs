@@ -0,0 +1,97 @@
+use crate::{render::Keys, Error};
+use std::ops::Range;
+
+/**
+A template that has been parsed once, so it can be rendered many times
+
+This is rendered with the same lenient options as `Resolver::render`, but without any helpers.
+
+# Example
+```rust
+# use template::{markings::Args, CompiledTemplate};
+let template = CompiledTemplate::compile("hello ${name}!").unwrap();
+for name in &["alice", "bob"] {
+    let output = template.render(&Args::new().with("name", name));
+    assert_eq!(output, format!("hello {}!", name));
+}
+
+// values are written as they are, and missing keys are left in place
+let template = CompiledTemplate::compile("${a} ${b}").unwrap();
+assert_eq!(template.render(&Args::new().with("a", "${b}")), "${b} ${b}");
+
+assert!(matches!(
+    CompiledTemplate::compile("hello ${name"),
+    Err(template::Error::InvalidTemplate(..))
+));
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledTemplate {
+    template: String,
+    keys: Vec<String>,
+    // the span of each `${key}`, with the index of its key in `keys`
+    segments: Vec<(Range<usize>, usize)>,
+}
+
+impl CompiledTemplate {
+    /// Parses the `template`, returning the compiled template
+    ///
+    /// # Errors
+    /// - `Error::InvalidTemplate` if the template has mismatched or nested `${..}`
+    pub fn compile(template: impl Into<String>) -> Result<Self, Error> {
+        let template = template.into();
+        let mut keys = markings::Template::find_keys(&template)
+            .map_err(Error::InvalidTemplate)?
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+
+        // `find_keys` rejected the templates that `Keys` yields `None` for
+        let segments = Keys::new(&template)
+            .flatten()
+            .filter_map(|(span, key)| {
+                Some((span, keys.binary_search_by(|k| k.as_str().cmp(key)).ok()?))
+            })
+            .collect();
+
+        Ok(Self {
+            template,
+            keys,
+            segments,
+        })
+    }
+
+    /// Renders the template with these `args`
+    ///
+    /// Keys that aren't in `args` are left in the output
+    pub fn render(&self, args: &markings::Args<'_>) -> String {
+        let mut values = vec![None; self.keys.len()];
+        for (key, value) in args.iter() {
+            if let Ok(index) = self.keys.binary_search_by(|k| k.as_str().cmp(key)) {
+                values[index] = Some(value.as_str());
+            }
+        }
+
+        let mut output = String::with_capacity(self.template.len());
+        let mut last = 0;
+        for (span, index) in &self.segments {
+            output.push_str(&self.template[last..span.start]);
+            output.push_str(values[*index].unwrap_or(&self.template[span.clone()]));
+            last = span.end;
+        }
+        output.push_str(&self.template[last..]);
+        output
+    }
+
+    /// The keys in the template
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// The template string
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+}
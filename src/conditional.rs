@@ -0,0 +1,93 @@
+/**
+Expands the conditional sections in a template string
+
+This is applied before the `${key}` replacements are done.
+
+# Syntax
+- `${if key}` starts a section that is kept when `key` is true
+- `${else}` starts the section that is kept when `key` is false (this is optional)
+- `${endif}` ends the section
+
+`lookup` is used to get the value for a `key`. Keys that are not known (or are not a `bool`) should be treated as `false`.
+
+Sections can be nested. Any other `${key}` is left in place for the template.
+
+This returns `None` if there is an unterminated `${if}`, or a `${else}`/`${endif}` without a matching `${if}`.
+
+# Example
+```rust
+# use template::expand_conditionals;
+let input = "${if premium}thanks, ${name}!${else}upgrade?${endif}";
+let premium = expand_conditionals(input, |key| key == "premium").unwrap();
+assert_eq!(premium, "thanks, ${name}!");
+
+let free = expand_conditionals(input, |_| false).unwrap();
+assert_eq!(free, "upgrade?");
+```
+*/
+pub fn expand_conditionals(input: &str, lookup: impl Fn(&str) -> bool) -> Option<String> {
+    // (the condition, whether we're in the else branch)
+    let mut stack: Vec<(bool, bool)> = vec![];
+    let emitting = |stack: &[(bool, bool)]| stack.iter().all(|&(cond, branch)| cond != branch);
+
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(head) = rest.find("${") {
+        let tail = match rest[head..].find('}') {
+            Some(tail) => head + tail,
+            None => break,
+        };
+
+        if emitting(&stack) {
+            output.push_str(&rest[..head]);
+        }
+
+        match rest[head + 2..tail].trim() {
+            key if key.starts_with("if ") => {
+                let key = key["if ".len()..].trim();
+                stack.push((lookup(key), false));
+            }
+            "else" => match stack.last_mut() {
+                Some((_, branch @ false)) => *branch = true,
+                _ => return None,
+            },
+            "endif" => {
+                stack.pop()?;
+            }
+            _ if emitting(&stack) => output.push_str(&rest[head..=tail]),
+            _ => {}
+        }
+
+        rest = &rest[tail + 1..];
+    }
+
+    if !stack.is_empty() {
+        return None;
+    }
+
+    output.push_str(rest);
+    Some(output)
+}
+
+/// Whether the input has any conditional sections, finding the keys the same way `expand_conditionals` does
+pub(crate) fn has_conditionals(input: &str) -> bool {
+    let mut rest = input;
+    while let Some(head) = rest.find("${") {
+        let tail = match rest[head..].find('}') {
+            Some(tail) => head + tail,
+            None => return false,
+        };
+        if is_conditional(&rest[head + 2..tail]) {
+            return true;
+        }
+        rest = &rest[tail + 1..];
+    }
+    false
+}
+
+/// Whether this key is part of a conditional section, rather than a replacement
+pub(crate) fn is_conditional(key: &str) -> bool {
+    let key = key.trim();
+    key.starts_with("if ") || key == "else" || key == "endif"
+}
@@ -0,0 +1,53 @@
+/// A template string with some optional metadata
+///
+/// This can be deserialized from either a plain string, or from an object with a `text` field
+/// and an optional `description` field.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(from = "Repr")]
+pub struct TemplateEntry {
+    /// The template string
+    pub text: String,
+    /// An optional description of the template
+    pub description: Option<String>,
+}
+
+impl TemplateEntry {
+    /// Get the template string
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Consume this entry, returning the template string
+    pub fn into_text(self) -> String {
+        self.text
+    }
+}
+
+impl AsRef<str> for TemplateEntry {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Repr {
+    Text(String),
+    Entry {
+        text: String,
+        #[serde(default)]
+        description: Option<String>,
+    },
+}
+
+impl From<Repr> for TemplateEntry {
+    fn from(repr: Repr) -> Self {
+        match repr {
+            Repr::Text(text) => Self {
+                text,
+                description: None,
+            },
+            Repr::Entry { text, description } => Self { text, description },
+        }
+    }
+}
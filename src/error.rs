@@ -3,25 +3,113 @@
 #[non_exhaustive]
 pub enum Error {
     /// An I/O error occurred
-    Io(std::io::Error),
+    Io {
+        /// The file that caused the error, if there was one
+        path: Option<std::path::PathBuf>,
+        /// The I/O error
+        source: std::io::Error,
+    },
     /// Serialization error
     Serialize(Box<dyn std::error::Error + Sync + Send>),
     /// Deserialization error
     Deserialize(Box<dyn std::error::Error + Sync + Send>),
+    /// The template map had more entries than allowed
+    TooManyEntries {
+        /// How many entries there were
+        entries: usize,
+        /// How many entries are allowed
+        max: usize,
+    },
+    /// The template for this `namespace.variant` key was not found
+    NotFound(String),
+    /// The template for this `namespace.variant` key could not be applied
+    Apply(String),
+    /// The template could not be compiled
+    InvalidTemplate(markings::Error),
+    /// The template for this `namespace.variant` key references itself
+    RecursiveTemplate(String),
+    /// The environment variable referenced by a template was not set
+    MissingEnv(String),
 }
 
 impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
-        Self::Io(err)
+        Self::Io {
+            path: None,
+            source: err,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Deserialize(Box::new(err))
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<serde_toml::de::Error> for Error {
+    fn from(err: serde_toml::de::Error) -> Self {
+        Self::Deserialize(Box::new(err))
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<serde_toml::ser::Error> for Error {
+    fn from(err: serde_toml::ser::Error) -> Self {
+        Self::Serialize(Box::new(err))
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<serde_yaml::Error> for Error {
+    fn from(err: serde_yaml::Error) -> Self {
+        Self::Deserialize(Box::new(err))
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl From<rmp_serde::decode::Error> for Error {
+    fn from(err: rmp_serde::decode::Error) -> Self {
+        Self::Deserialize(Box::new(err))
+    }
+}
+
+#[cfg(feature = "figment")]
+impl From<figment::Error> for Error {
+    fn from(err: figment::Error) -> Self {
+        Self::Deserialize(Box::new(err))
+    }
+}
+
+impl Error {
+    pub(crate) fn io_at(path: impl Into<std::path::PathBuf>, source: std::io::Error) -> Self {
+        Self::Io {
+            path: Some(path.into()),
+            source,
+        }
     }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Io(io) => write!(f, "io error: {}", io),
+            Self::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "io error ({}): {}", path.display(), source),
+            Self::Io { source, .. } => write!(f, "io error: {}", source),
             Self::Serialize(ser) => write!(f, "serialize error: {}", ser),
             Self::Deserialize(de) => write!(f, "deserialize error: {}", de),
+            Self::TooManyEntries { entries, max } => {
+                write!(f, "too many entries: {} (max: {})", entries, max)
+            }
+            Self::NotFound(key) => write!(f, "template not found: {}", key),
+            Self::Apply(key) => write!(f, "cannot apply template: {}", key),
+            Self::InvalidTemplate(err) => write!(f, "invalid template: {}", err),
+            Self::RecursiveTemplate(key) => write!(f, "recursive template reference: {}", key),
+            Self::MissingEnv(name) => write!(f, "environment variable not set: {}", name),
         }
     }
 }
@@ -29,8 +117,14 @@ impl std::fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::Io(err) => Some(err),
+            Self::Io { source, .. } => Some(source),
             Self::Serialize(err) | Self::Deserialize(err) => Some(&**err),
+            Self::InvalidTemplate(err) => Some(err),
+            Self::TooManyEntries { .. }
+            | Self::NotFound(..)
+            | Self::Apply(..)
+            | Self::RecursiveTemplate(..)
+            | Self::MissingEnv(..) => None,
         }
     }
 }
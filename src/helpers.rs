@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+/// A helper that transforms a rendered value
+pub type HelperFunction = fn(&str) -> String;
+
+/**
+A registry of named helpers, used by `Resolver::render` for keys like `${name|upper}`
+
+The helpers are applied left to right to the value bound to the key before the first `|`,
+e.g. `${name|trim|upper}`. Keys without a `|` are bound as usual.
+
+# Built-in helpers
+- `upper`: converts the value to uppercase
+- `lower`: converts the value to lowercase
+- `trim`: removes the leading and trailing whitespace
+- `truncate:N`: keeps the first `N` characters
+
+A key with an unknown helper isn't bound, so it's left in the output.
+
+# Example
+```rust
+# use template::{markings::Args, HelperRegistry};
+let mut helpers = HelperRegistry::new();
+helpers.register("shout", |input| format!("{}!", input.to_uppercase()));
+
+let template = "${name|upper} ${name|truncate:3} ${name|shout}";
+let args = helpers.bind(template, Args::new().with("name", "world"));
+let opts = template::markings::Opts::default().optional_keys().build();
+let template = template::markings::Template::parse(template, opts).unwrap();
+assert_eq!(template.apply(&args).unwrap(), "WORLD wor WORLD!");
+```
+*/
+#[derive(Clone)]
+pub struct HelperRegistry {
+    helpers: HashMap<String, HelperFunction>,
+}
+
+impl std::fmt::Debug for HelperRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names = self.helpers.keys().collect::<Vec<_>>();
+        names.sort();
+        f.debug_struct("HelperRegistry")
+            .field("helpers", &names)
+            .finish()
+    }
+}
+
+impl Default for HelperRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HelperRegistry {
+    /// Create a new registry with the built-in helpers
+    pub fn new() -> Self {
+        let mut this = Self {
+            helpers: HashMap::new(),
+        };
+        this.register("upper", str::to_uppercase);
+        this.register("lower", str::to_lowercase);
+        this.register("trim", |input| input.trim().to_string());
+        this
+    }
+
+    /// Register a helper with this `name`, returning the helper it replaced
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        helper: HelperFunction,
+    ) -> Option<HelperFunction> {
+        self.helpers.insert(name.into(), helper)
+    }
+
+    /// Binds the keys with helpers in `template`, using the values in `args`
+    pub fn bind<'k>(&self, template: &str, args: markings::Args<'k>) -> markings::Args<'k> {
+        let keys = match markings::Template::find_keys(template) {
+            Ok(keys) => keys,
+            Err(..) => return args,
+        };
+
+        let mut bound = vec![];
+        for key in keys.into_iter().filter(|key| key.contains('|')) {
+            let mut helpers = key.split('|');
+            let name = helpers.next().unwrap_or_default().trim();
+            let value = match args.iter().find(|(k, _)| k.as_ref() == name) {
+                Some((_, value)) => value.clone(),
+                None => continue,
+            };
+            if let Some(value) = helpers.try_fold(value, |value, helper| self.call(helper, &value))
+            {
+                bound.push((key.to_string(), value));
+            }
+        }
+
+        bound
+            .into_iter()
+            .fold(args, |args, (key, value)| args.with(key, value))
+    }
+
+    fn call(&self, helper: &str, input: &str) -> Option<String> {
+        match helper.split_once(':') {
+            Some((name, len)) if name.trim() == "truncate" => {
+                let len = len.trim().parse().ok()?;
+                Some(input.chars().take(len).collect())
+            }
+            Some(..) => None,
+            None => self.helpers.get(helper.trim()).map(|helper| helper(input)),
+        }
+    }
+}
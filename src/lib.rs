@@ -17,8 +17,11 @@
 #[doc(inline)]
 pub use markings;
 
+#[macro_use]
+mod macros;
+
 mod mapping;
-pub use mapping::Mapping;
+pub use mapping::{Mapping, TemplateDiff, TemplateMap};
 
 mod templates;
 pub use templates::Templates;
@@ -27,16 +30,67 @@ mod error;
 pub use error::Error;
 
 mod store;
-pub use store::{FileStore, MemoryStore, NullStore, PartialStore, TemplateStore};
+pub use store::{
+    FileStore, FnStore, KeyNormalizer, LimitedStore, MapKeysStore, MemoryStore, MergedStore,
+    NullStore, PartialStore, RetryStore, Source, StaticStore, StdinStore, TemplateStore,
+    TimedStore, TrimStore,
+};
+
+#[cfg(feature = "env-interp")]
+pub use store::EnvStore;
+
+#[cfg(feature = "figment")]
+pub use store::FigmentStore;
+
+#[cfg(feature = "include_dir")]
+pub use store::IncludeDirStore;
+
+#[cfg(feature = "zip")]
+pub use store::ArchiveStore;
 
 mod loader;
 pub use loader::*;
 
-/// A template mapping of `T` to `Mapping<T>`
-pub type TemplateMap<T> = std::collections::HashMap<T, Mapping<T>>;
+mod stats;
+pub use stats::ResolverStats;
+
+mod readonly;
+pub use readonly::ReadOnlyResolver;
+
+mod compiled;
+pub use compiled::CompiledTemplate;
+
+mod pool;
+pub use pool::{PooledString, RenderPool};
+
+mod conditional;
+pub use conditional::expand_conditionals;
+
+mod render;
+pub use render::apply_fields;
+
+#[cfg(feature = "async")]
+mod watch;
+#[cfg(feature = "async")]
+pub use watch::ReloadEvent;
+
+#[cfg(feature = "helpers")]
+mod helpers;
+#[cfg(feature = "helpers")]
+pub use helpers::{HelperFunction, HelperRegistry};
+
+#[cfg(feature = "lru")]
+mod cache;
+
+#[cfg(feature = "entries")]
+mod entry;
+#[cfg(feature = "entries")]
+pub use entry::TemplateEntry;
+
+/// A template mapping of `T` to `Mapping<T, V>`, as a bare `HashMap`
+#[deprecated(note = "use the `TemplateMap` newtype instead")]
+pub type TemplateHashMap<T, V = T> = std::collections::HashMap<T, Mapping<T, V>>;
 
-#[cfg(feature = "derive")]
-#[allow(unused_imports)]
 #[cfg(feature = "derive")]
 extern crate template_derive;
 
@@ -45,11 +99,12 @@ extern crate template_derive;
 pub use template_derive::*;
 
 /**
-Template for applying a templated string to an enum variant
+Template for applying a templated string to an enum variant (or a struct)
 
 # Example using the derive feature
-```rust,ignore
-use template::Template;
+```rust
+# #[cfg(feature = "derive")] {
+use template::{NameCasing, Template};
 // first, derive Template
 #[derive(Template, Debug)]
 // then add a namespace, this it the 'section' (or 'object') containing each template for this type
@@ -63,24 +118,45 @@ enum MyResponse<'a> {
     CountItems { count: usize },
     // fieldless variants don't have variables
     Okay,
+    // bool fields can be used in conditional sections
+    Status { premium: bool },
+    // a single unnamed field wrapping another template delegates to it
+    Error(MyError),
+}
+
+#[derive(Template, Debug)]
+#[namespace("error")]
+enum MyError {
+    NotFound { id: usize },
 }
 
 let hello = MyResponse::Hello { name: "world" };
 let count = MyResponse::CountItems { count: 42 };
 let okay = MyResponse::Okay;
 
-assert_eq!(MyResponse::namespace(), "response");
+assert_eq!(MyResponse::namespace(NameCasing::Snake), "response");
 // snake_case of the enum
-assert_eq!(MyResponse::name(), "my_response");
+assert_eq!(MyResponse::name(NameCasing::Snake), "my_response");
 
 // snake_case of variant
-assert_eq!(hello.variant(), "hello");
-assert_eq!(count.variant(), "count_items");
-assert_eq!(okay.variant(), "okay");
+assert_eq!(hello.variant(NameCasing::Snake), "hello");
+assert_eq!(count.variant(NameCasing::Snake), "count_items");
+assert_eq!(okay.variant(NameCasing::Snake), "okay");
 
 assert_eq!(hello.apply("hello ${name}!").unwrap(), "hello world!");
 assert_eq!(count.apply("count is: ${count}").unwrap(), "count is: 42");
 assert_eq!(okay.apply("okay response").unwrap(), "okay response");
+
+// see `expand_conditionals` for the syntax
+let status = MyResponse::Status { premium: true };
+assert_eq!(status.apply("${if premium}thanks!${else}upgrade?${endif}").unwrap(), "thanks!");
+
+// wrapped templates use the inner namespace and variant
+let error = MyResponse::Error(MyError::NotFound { id: 42 });
+assert_eq!(error.variant_namespace(NameCasing::Snake), "error");
+assert_eq!(error.variant(NameCasing::Snake), "not_found");
+assert_eq!(error.apply("${id} was not found").unwrap(), "42 was not found");
+# }
 ```
 
 ## Example of how you could store the templates in textual form:
@@ -112,41 +188,257 @@ response:
 ```
 */
 pub trait Template {
+    /// The default templates of the variants, as `(key, template)`
+    ///
+    /// The keys are joined with `separator`. This is empty by default, the derive lists every variant with a `#[template("...")]` attribute
+    const TEMPLATES: &'static [(&'static str, &'static str)] = &[];
+    /// The template used by the resolvers' `apply` when a variant has no template
+    ///
+    /// This is `None` by default, the derive sets it with `#[template_default(Variant)]` or `#[template_default("...")]`
+    const FALLBACK: Option<Fallback> = None;
     /// Namespace of the template
     fn namespace(casing: NameCasing) -> &'static str;
     /// Name of the template (the enum's name)
     fn name(casing: NameCasing) -> &'static str;
+    /// The casing the resolvers look up the templates of this type with
+    ///
+    /// This is snake case by default, the derive can change it with `#[namespace("name", casing = "kebab")]`
+    fn declared_casing() -> NameCasing {
+        NameCasing::Snake
+    }
+    /// The casing the resolvers look up the template of the specific variant with
+    ///
+    /// This is `declared_casing` by default, the derive uses the inner type's for wrapped templates
+    fn variant_casing(&self) -> NameCasing {
+        Self::declared_casing()
+    }
     /// Name of the specific variant
     fn variant(&self, casing: NameCasing) -> &'static str;
+    /// Namespace of the specific variant
+    ///
+    /// This is the type's `namespace` by default, the derive uses the inner namespace for wrapped templates
+    fn variant_namespace(&self, casing: NameCasing) -> &'static str {
+        Self::namespace(casing)
+    }
+    /// Names of all of the variants
+    ///
+    /// This is empty by default, the derive lists every variant that doesn't wrap another template
+    fn variants(casing: NameCasing) -> &'static [&'static str] {
+        let _ = casing;
+        &[]
+    }
+    /// Separator between the namespace and the variant in a key
+    ///
+    /// This is `.` by default, the derive can change it with `#[separator("/")]`
+    fn separator() -> &'static str {
+        "."
+    }
+    /// The `namespace.variant` key of the specific variant, joined with `separator`
+    fn key(&self, casing: NameCasing) -> String {
+        [
+            self.variant_namespace(casing),
+            Self::separator(),
+            self.variant(casing),
+        ]
+        .concat()
+    }
+    /// Names of the fields of the specific variant
+    ///
+    /// This is empty by default, the derive lists the fields. It is used by `apply_debug` and `apply_strict`
+    fn fields(&self) -> &'static [&'static str] {
+        &[]
+    }
     /// Apply this template string to this variant
     fn apply(&self, input: &str) -> Option<String>;
+    /// Apply this template string to this variant, returning `input` unchanged if it could not be applied
+    fn apply_or_raw(&self, input: &str) -> String {
+        self.apply(input).unwrap_or_else(|| input.to_string())
+    }
+    /// Apply this template string to this variant, writing the output to `out`
+    ///
+    /// This returns whether the template could be applied. The default writes the output of `apply`,
+    /// the derive writes the fields straight to `out` with `apply_fields`, without building the output first.
+    ///
+    /// # Errors
+    /// - Any error from writing to `out`
+    fn apply_fmt(
+        &self,
+        input: &str,
+        out: &mut dyn std::fmt::Write,
+    ) -> Result<bool, std::fmt::Error> {
+        match self.apply(input) {
+            Some(output) => out.write_str(&output).map(|_| true),
+            None => Ok(false),
+        }
+    }
+    /// Apply this template string to this variant, into a buffer from `pool`
+    ///
+    /// This renders with `apply_fmt`, so a derived template doesn't allocate once the pool is warm.
+    fn apply_pooled<'p>(&self, input: &str, pool: &'p RenderPool) -> Option<PooledString<'p>> {
+        let mut buf = pool.take();
+        match self.apply_fmt(input, &mut *buf) {
+            Ok(true) => Some(buf),
+            _ => None,
+        }
+    }
+    /// Apply this template string to this variant, returning the output as bytes
+    fn apply_bytes(&self, input: &str) -> Option<Vec<u8>> {
+        self.apply(input).map(String::into_bytes)
+    }
+    /// Apply this template string to this variant, writing the output to `out`
+    ///
+    /// This returns whether the template could be applied, nothing is written if it couldn't.
+    ///
+    /// # Errors
+    /// - Any I/O error from writing to `out`
+    fn apply_to<W: std::io::Write>(&self, input: &str, mut out: W) -> std::io::Result<bool> {
+        match self.apply(input) {
+            Some(output) => out.write_all(output.as_bytes()).map(|_| true),
+            None => Ok(false),
+        }
+    }
+    /// Apply this template string to this variant, also returning the keys in the template that this variant doesn't bind
+    fn apply_debug(&self, input: &str) -> (Option<String>, Vec<String>) {
+        let fields = self.fields();
+        let mut missing: Vec<String> = vec![];
+        for key in markings::Template::find_keys(input).unwrap_or_default() {
+            if !conditional::is_conditional(key)
+                && !fields.contains(&key)
+                && !missing.iter().any(|k| k == key)
+            {
+                missing.push(key.to_string())
+            }
+        }
+        (self.apply(input), missing)
+    }
+    /// Apply this template string to this variant, failing if the template has any keys this variant doesn't bind
+    fn apply_strict(&self, input: &str) -> Option<String> {
+        match self.apply_debug(input) {
+            (output, missing) if missing.is_empty() => output,
+            _ => None,
+        }
+    }
+    /// Apply this template string to this variant, handling output longer than `max_len` bytes with `overflow`
+    ///
+    /// This renders with `apply_fmt`, stopping once the output passes `max_len`, so a derived template never builds more than `max_len` bytes.
+    /// The default `apply_fmt` of a hand-written template builds the whole output with `apply` first.
+    fn apply_limited(&self, input: &str, max_len: usize, overflow: Overflow) -> Option<String> {
+        let mut output = String::new();
+        let mut limited = render::Limited {
+            out: &mut output,
+            max_len,
+            overflowed: false,
+        };
+        match self.apply_fmt(input, &mut limited) {
+            Ok(true) => return Some(output),
+            Err(..) if limited.overflowed => {}
+            _ => return None,
+        }
+        match overflow {
+            Overflow::Fail => None,
+            Overflow::Truncate(marker) => {
+                let mut end = max_len.saturating_sub(marker.len());
+                while !output.is_char_boundary(end) {
+                    end -= 1;
+                }
+                output.truncate(end);
+                output.push_str(marker);
+                Some(output)
+            }
+        }
+    }
 }
 
 /// The casing to get for the Templates parsed state
-#[derive(Debug, Copy, Clone, PartialEq, Hash, Eq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Hash, Eq)]
 #[non_exhaustive]
 pub enum NameCasing {
     /// Snake case (default)
+    #[default]
     Snake,
     /// The origianl parsed case
     Original,
+    /// Kebab case
+    Kebab,
 }
 
-impl Default for NameCasing {
-    fn default() -> Self {
-        Self::Snake
+/// The namespace-wide fallback of a `Template`, see `Template::FALLBACK`
+#[derive(Debug, Copy, Clone, PartialEq, Hash, Eq)]
+#[non_exhaustive]
+pub enum Fallback {
+    /// The template of this variant, in the type's namespace (using `Template::declared_casing`)
+    Variant(&'static str),
+    /// This template
+    Template(&'static str),
+}
+
+/// What `Template::apply_limited` does with output that's too long
+///
+/// # Example
+/// ```rust
+/// # use template::{Overflow, Template, NameCasing};
+/// # struct Long;
+/// # impl Template for Long {
+/// #     fn namespace(_: NameCasing) -> &'static str { "long" }
+/// #     fn name(_: NameCasing) -> &'static str { "long" }
+/// #     fn variant(&self, _: NameCasing) -> &'static str { "long" }
+/// #     fn apply(&self, input: &str) -> Option<String> { Some(input.repeat(10)) }
+/// # }
+/// assert_eq!(Long.apply_limited("ab", 8, Overflow::Fail), None);
+/// assert_eq!(Long.apply_limited("ab", 8, Overflow::Truncate("...")).unwrap(), "ababa...");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Hash, Eq)]
+#[non_exhaustive]
+pub enum Overflow {
+    /// Fail to apply the template
+    Fail,
+    /// Truncate the output, ending it with this marker
+    ///
+    /// The output, including the marker, fits in the limit (unless the marker itself is longer)
+    Truncate(&'static str),
+}
+
+/// How a `Resolver` treats templates that are empty, or only whitespace
+#[derive(Debug, Default, Copy, Clone, PartialEq, Hash, Eq)]
+#[non_exhaustive]
+pub enum EmptyPolicy {
+    /// Empty templates are used, and render as empty (default)
+    #[default]
+    Allow,
+    /// Empty templates are treated as missing, so the fallbacks are used
+    TreatAsMissing,
+}
+
+impl EmptyPolicy {
+    fn allows(self, template: &str) -> bool {
+        match self {
+            Self::Allow => true,
+            Self::TreatAsMissing => !template.trim().is_empty(),
+        }
     }
 }
 
 /// A Template Resolver
 ///
 /// Provides a simple way to always get the latest template string for a `namespace.variant`
+///
+/// A resolver is `Send` and `Sync` when its store is, which all of the built-in stores are
+/// (a `FnStore` is when its function is).
 #[derive(Debug)]
 pub struct Resolver<S>
 where
     S: TemplateStore,
 {
     templates: Templates<S>,
+    default_locale: Option<String>,
+    empty_policy: EmptyPolicy,
+    #[cfg(feature = "async")]
+    reloads: tokio::sync::broadcast::Sender<ReloadEvent>,
+    #[cfg(feature = "helpers")]
+    helpers: HelperRegistry,
+    #[cfg(feature = "lru")]
+    cache: Option<cache::RenderCache>,
+    stats: stats::Counters,
 }
 
 impl<S: TemplateStore> Resolver<S> {
@@ -155,25 +447,472 @@ impl<S: TemplateStore> Resolver<S> {
     /// # Errors
     /// - Failure to load/parse the initial templates
     pub fn new(store: S) -> Result<Self, Error> {
-        Templates::new(store).map(|templates| Self { templates })
+        Templates::new(store).map(|templates| Self {
+            templates,
+            default_locale: None,
+            empty_policy: EmptyPolicy::Allow,
+            #[cfg(feature = "async")]
+            reloads: watch::channel(),
+            #[cfg(feature = "helpers")]
+            helpers: HelperRegistry::new(),
+            #[cfg(feature = "lru")]
+            cache: None,
+            stats: stats::Counters::default(),
+        })
     }
 
     /// Tries to get the template string for `namespace.variant`
-    pub fn resolve(&mut self, namespace: &str, variant: &str) -> Option<&String> {
-        self.templates
-            .refresh()
-            .map_err(|err| {
-                log::warn!(
-                    "Cannot refresh templates ({}::{}): {}",
-                    namespace,
-                    variant,
-                    err
-                );
-                err
-            })
+    ///
+    /// The `namespace` and `variant` can be anything that's `AsRef<str>`, e.g. `&str`, `String` or `Cow<str>`
+    pub fn resolve(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+    ) -> Option<&String> {
+        let (namespace, variant) = (namespace.as_ref(), variant.as_ref());
+        self.refresh_for(format_args!("{}::{}", namespace, variant))
+            .ok()?;
+        self.lookup(namespace, variant)
+    }
+
+    /// Gets the template string for `namespace.variant`
+    ///
+    /// # Errors
+    /// - Failure to refresh the templates
+    /// - The template was not found
+    pub fn try_resolve(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+    ) -> Result<&String, Error> {
+        let (namespace, variant) = (namespace.as_ref(), variant.as_ref());
+        self.refresh_for(format_args!("{}::{}", namespace, variant))?;
+        self.lookup(namespace, variant)
+            .ok_or_else(|| Error::NotFound(format!("{}.{}", namespace, variant)))
+    }
+
+    /// Tries to get the bytes of the template string for `namespace.variant`
+    ///
+    /// This borrows the template, see `resolve`
+    pub fn resolve_bytes(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+    ) -> Option<&[u8]> {
+        self.resolve(namespace, variant).map(String::as_bytes)
+    }
+
+    /// Tries to get the template string for `namespace.variant`, without refreshing the templates
+    ///
+    /// Unlike `resolve`, this only needs `&self`. Use `refresh` to pick up changes from the store
+    pub fn get(&self, namespace: impl AsRef<str>, variant: impl AsRef<str>) -> Option<&String> {
+        self.lookup(namespace.as_ref(), variant.as_ref())
+    }
+
+    /// Tries to get the template string for `namespace.variant`, and which layer of the store it came from
+    ///
+    /// See `TemplateStore::source`
+    pub fn resolve_with_source(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+    ) -> Option<(&String, Source)> {
+        let (namespace, variant) = (namespace.as_ref(), variant.as_ref());
+        self.refresh_for(format_args!("{}::{}", namespace, variant))
+            .ok()?;
+        let template = self.lookup(namespace, variant)?;
+        Some((template, self.templates.store().source(namespace, variant)))
+    }
+
+    fn lookup(&self, namespace: &str, variant: &str) -> Option<&String> {
+        let template = self
+            .templates
+            .get(namespace)
+            .and_then(|mapping| mapping.get(variant))
+            .filter(|template| self.empty_policy.allows(template));
+        self.stats.lookup(template.is_some());
+        template
+    }
+
+    /// Tries to get the template string for a `namespace.variant` key, see `parse_key`
+    pub fn resolve_key(&mut self, key: &str) -> Option<&String> {
+        let (namespace, variant) = parse_key(key)?;
+        self.resolve(namespace, variant)
+    }
+
+    /// Tries to get the template string for every variant of `T`, see `Template::variants`
+    ///
+    /// The variants without a template map to `None`
+    pub fn resolve_many_for<T: Template>(
+        &mut self,
+    ) -> std::collections::BTreeMap<&'static str, Option<&String>> {
+        let namespace = T::namespace(T::declared_casing());
+        let _ = self.refresh_for(namespace);
+        let this = &*self;
+        T::variants(T::declared_casing())
+            .iter()
+            .map(|variant| (*variant, this.lookup(namespace, variant)))
+            .collect()
+    }
+
+    /// Gets the template string for `namespace.variant`, inserting the template produced by `f` if it was missing
+    ///
+    /// The inserted template is only kept in memory. When the store reports a change
+    /// to this namespace, the refresh replaces it, dropping the inserted template
+    /// (and `f` will be called again if the store doesn't provide it).
+    pub fn get_or_insert_with(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+        f: impl FnOnce() -> String,
+    ) -> &String {
+        let (namespace, variant) = (namespace.as_ref(), variant.as_ref());
+        let _ = self.refresh_for(format_args!("{}::{}", namespace, variant));
+        self.templates.get_or_insert_with(namespace, variant, f)
+    }
+
+    /// Tries to apply the template for this `value`, expanding any references to other templates
+    ///
+    /// See `Templates::expand_references` for the reference syntax.
+    ///
+    /// When the template for `value` is missing, `T::FALLBACK` is used instead.
+    ///
+    /// This returns `Ok(None)` if the template for `value` (and its fallback) is missing, or could not be applied
+    ///
+    /// # Errors
+    /// - A referenced template was not found
+    /// - A template references itself, directly or through other templates
+    pub fn apply<T: Template>(&mut self, value: &T) -> Result<Option<String>, Error> {
+        let casing = value.variant_casing();
+        let namespace = value.variant_namespace(casing);
+        let variant = value.variant(casing);
+        let template = match self.resolve_owned(namespace, variant) {
+            Some(template) => template,
+            None => match self.fallback::<T>() {
+                Some(template) => template,
+                None => return Ok(None),
+            },
+        };
+
+        let template = self.templates.expand_references(&template)?;
+        Ok(value.apply(&template))
+    }
+
+    /// Applies the template for this `value`, expanding any references to other templates
+    ///
+    /// Unlike `apply`, a missing template (without a fallback) or one that could not be applied is an error
+    ///
+    /// # Errors
+    /// - Failure to refresh the templates
+    /// - The template, or a referenced template, was not found
+    /// - A template references itself, directly or through other templates
+    /// - The template could not be applied
+    pub fn try_apply<T: Template>(&mut self, value: &T) -> Result<String, Error> {
+        let casing = value.variant_casing();
+        let namespace = value.variant_namespace(casing);
+        let variant = value.variant(casing);
+        let template = match self.try_resolve(namespace, variant) {
+            Ok(template) => template.clone(),
+            Err(Error::NotFound(key)) => self.fallback::<T>().ok_or(Error::NotFound(key))?,
+            Err(err) => return Err(err),
+        };
+        let template = self.templates.expand_references(&template)?;
+        value
+            .apply(&template)
+            .ok_or_else(|| Error::Apply(format!("{}.{}", namespace, variant)))
+    }
+
+    fn fallback<T: Template>(&mut self) -> Option<String> {
+        match T::FALLBACK? {
+            Fallback::Variant(variant) => {
+                self.resolve_owned(T::namespace(T::declared_casing()), variant)
+            }
+            Fallback::Template(template) => Some(template.to_string()),
+        }
+    }
+
+    /// Tries to get the `(namespace, variant, template)` that `apply` would use for this `value`, without rendering it
+    pub fn preview<T: Template>(&mut self, value: &T) -> Option<(String, String, &String)> {
+        let casing = value.variant_casing();
+        let namespace = value.variant_namespace(casing);
+        let variant = value.variant(casing);
+        let template = self.resolve(namespace, variant)?;
+        Some((namespace.to_string(), variant.to_string(), template))
+    }
+
+    /// Tries to get the template for `namespace.variant`, compiled so it can be rendered many times
+    ///
+    /// The compiled template is a copy, it isn't updated when the templates are refreshed.
+    pub fn resolve_compiled(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+    ) -> Option<CompiledTemplate> {
+        let template = self.resolve(namespace, variant)?;
+        CompiledTemplate::compile(template.as_str()).ok()
+    }
+
+    /// Tries to render the template for `namespace.variant` with these `args`
+    ///
+    /// This uses the same lenient options as the derive
+    ///
+    /// With the `helpers` feature, keys like `${name|upper}` are bound using `helpers`
+    ///
+    /// With the `lru` feature, the output can be cached, see `with_render_cache`
+    pub fn render(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+        args: markings::Args<'_>,
+    ) -> Option<String> {
+        self.try_render(namespace, variant, args).ok()
+    }
+
+    /// Tries to render the template for `namespace.variant`, binding `${0}`, `${1}`, ... to these `args`
+    ///
+    /// See `render`
+    pub fn render_positional(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+        args: &[&dyn std::fmt::Display],
+    ) -> Option<String> {
+        let args = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| (i.to_string(), arg))
+            .collect();
+        self.render(namespace, variant, args)
+    }
+
+    /// Renders the template for `namespace.variant` with these `args`, see `render`
+    ///
+    /// # Errors
+    /// - Failure to refresh the templates
+    /// - The template was not found
+    /// - The template could not be parsed or rendered
+    pub fn try_render(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+        args: markings::Args<'_>,
+    ) -> Result<String, Error> {
+        let (namespace, variant) = (namespace.as_ref(), variant.as_ref());
+        self.refresh_for(format_args!("{}::{}", namespace, variant))?;
+
+        #[cfg(feature = "lru")]
+        let key = match &mut self.cache {
+            Some(cache) => {
+                let key = cache::RenderCache::key(namespace, variant, &args);
+                if let Some(output) = cache.get(&key, self.templates.version()) {
+                    self.stats.lookup(true);
+                    return Ok(output.clone());
+                }
+                Some(key)
+            }
+            None => None,
+        };
+
+        let name = || format!("{}.{}", namespace, variant);
+        let template = self
+            .lookup(namespace, variant)
+            .ok_or_else(|| Error::NotFound(name()))?;
+        #[cfg(feature = "helpers")]
+        let args = self.helpers.bind(template, args);
+        let output = render_str(template, args).ok_or_else(|| Error::Apply(name()))?;
+
+        #[cfg(feature = "lru")]
+        if let (Some(cache), Some(key)) = (&mut self.cache, key) {
+            cache.insert(key, output.clone());
+        }
+        Ok(output)
+    }
+
+    /// Cache the output of `render`, keeping up to `capacity` of the most recently used outputs
+    ///
+    /// The outputs are keyed by the `namespace.variant` and the args, so this helps when
+    /// the same args are rendered repeatedly. A cached render skips the parsing and rendering of the
+    /// template, at the cost of keeping each output (and its key, with a copy of the args) in memory.
+    /// The cache is cleared whenever the templates change.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use template::{markings::Args, MemoryStore, Resolver};
+    /// let store = MemoryStore::new("greet.hello = hello ${a} ${b}", template::load_lines);
+    /// let mut resolver = Resolver::new(store)
+    ///     .unwrap()
+    ///     .with_render_cache(std::num::NonZeroUsize::new(8).unwrap());
+    ///
+    /// let args = Args::new().with("a", "x").with("b", "y");
+    /// assert_eq!(resolver.render("greet", "hello", args).unwrap(), "hello x y");
+    /// // different args never get a cached output of other args
+    /// let args = Args::new().with("a", "y").with("b", "x");
+    /// assert_eq!(resolver.render("greet", "hello", args).unwrap(), "hello y x");
+    /// ```
+    #[cfg(feature = "lru")]
+    pub fn with_render_cache(mut self, capacity: std::num::NonZeroUsize) -> Self {
+        self.set_render_cache(Some(capacity));
+        self
+    }
+
+    /// Set the capacity of the `render` cache, or disable it with `None`. See `with_render_cache`
+    #[cfg(feature = "lru")]
+    pub fn set_render_cache(&mut self, capacity: Option<std::num::NonZeroUsize>) {
+        self.cache = capacity.map(cache::RenderCache::new);
+    }
+
+    // the cached outputs depend on more than the templates
+    fn clear_render_cache(&mut self) {
+        #[cfg(feature = "lru")]
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Gets the template string for `namespace.variant`, or `default` if it is missing
+    pub fn resolve_with_default<'a>(
+        &'a mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+        default: &'a str,
+    ) -> &'a str {
+        self.resolve(namespace, variant)
+            .map(String::as_str)
+            .unwrap_or(default)
+    }
+
+    /// Tries to get the template string for `namespace.variant` in a specific `locale`
+    ///
+    /// Localized templates are stored in a namespace prefixed by the locale, e.g. `en.response`
+    ///
+    /// The lookup falls back in this order:
+    /// - `locale.namespace`
+    /// - `default_locale.namespace`, if a default locale was set
+    /// - `namespace`
+    pub fn resolve_localized(
+        &mut self,
+        locale: impl AsRef<str>,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+    ) -> Option<&String> {
+        let (locale, namespace, variant) = (locale.as_ref(), namespace.as_ref(), variant.as_ref());
+        self.refresh_for(format_args!("{}.{}::{}", locale, namespace, variant))
             .ok()?;
+        let default_locale = self.default_locale.as_deref();
+        let template = resolve_localized(
+            &self.templates,
+            default_locale,
+            self.empty_policy,
+            locale,
+            namespace,
+            variant,
+        );
+        self.stats.lookup(template.is_some());
+        template
+    }
+
+    /// Set the default locale used as a fallback by `resolve_localized`
+    pub fn with_default_locale(mut self, locale: impl Into<String>) -> Self {
+        self.set_default_locale(locale);
+        self
+    }
+
+    /// Set the default locale used as a fallback by `resolve_localized`
+    pub fn set_default_locale(&mut self, locale: impl Into<String>) {
+        self.default_locale.replace(locale.into());
+    }
+
+    /// Get the default locale used as a fallback by `resolve_localized`
+    pub fn default_locale(&self) -> Option<&str> {
+        self.default_locale.as_deref()
+    }
 
-        self.templates.get(namespace)?.get(variant)
+    /// Set how empty templates are treated by the lookups, see `EmptyPolicy`
+    pub fn with_empty_policy(mut self, policy: EmptyPolicy) -> Self {
+        self.set_empty_policy(policy);
+        self
+    }
+
+    /// Set how empty templates are treated by the lookups, see `EmptyPolicy`
+    pub fn set_empty_policy(&mut self, policy: EmptyPolicy) {
+        self.clear_render_cache();
+        self.empty_policy = policy;
+    }
+
+    /// Get how empty templates are treated by the lookups
+    pub fn empty_policy(&self) -> EmptyPolicy {
+        self.empty_policy
+    }
+
+    /// Tries to get the template strings for each `(namespace, variant)` in `keys`
+    ///
+    /// This only refreshes the templates once for the entire batch
+    pub fn resolve_all(&mut self, keys: &[(&str, &str)]) -> Vec<Option<&String>> {
+        if self
+            .refresh_for(format_args!("{} keys", keys.len()))
+            .is_err()
+        {
+            return vec![None; keys.len()];
+        }
+
+        let this = &*self;
+        keys.iter()
+            .map(|(namespace, variant)| this.lookup(namespace, variant))
+            .collect()
+    }
+
+    /// Tries to get an owned copy of the template string for `namespace.variant`
+    ///
+    /// Unlike `resolve`, the borrow of the resolver ends immediately
+    pub fn resolve_owned(
+        &mut self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+    ) -> Option<String> {
+        self.resolve(namespace, variant).cloned()
+    }
+
+    /// Attach a new store to this resolver, loading the templates from it
+    ///
+    /// Once the store is loaded, its templates replace the current templates
+    ///
+    /// # Errors
+    /// - Failure to load/parse the templates from the new store
+    pub fn attach<T: TemplateStore>(self, store: T) -> Result<Resolver<T>, Error> {
+        Ok(Resolver {
+            templates: self.templates.attach(store)?,
+            default_locale: self.default_locale,
+            empty_policy: self.empty_policy,
+            #[cfg(feature = "async")]
+            reloads: self.reloads,
+            #[cfg(feature = "helpers")]
+            helpers: self.helpers,
+            #[cfg(feature = "lru")]
+            cache: self.cache,
+            stats: self.stats,
+        })
+    }
+
+    /// Inserts the template for `namespace.variant`, returning the previous template
+    ///
+    /// See `Templates::insert`
+    pub fn insert(
+        &mut self,
+        namespace: impl Into<String>,
+        variant: impl Into<String>,
+        template: impl Into<String>,
+    ) -> Option<String> {
+        self.templates.insert(namespace, variant, template)
+    }
+
+    /// Replace the inner store with `store`, loading the templates from it
+    ///
+    /// On failure the previous store and its templates are kept
+    ///
+    /// # Errors
+    /// - Failure to load/parse the templates from the new store
+    pub fn reload_from(&mut self, store: S) -> Result<(), Error> {
+        self.templates.reload_from(store)
     }
 
     /// Get a reference to the inner store
@@ -181,11 +920,118 @@ impl<S: TemplateStore> Resolver<S> {
         self.templates.store()
     }
 
+    /// The version of the templates, see `Templates::version`
+    ///
+    /// This does not refresh the templates. It can be used as an ETag for anything rendered from them.
+    pub fn version(&self) -> u64 {
+        self.templates.version()
+    }
+
+    /// Refreshes the templates from the store, returning whether any templates changed
+    ///
+    /// The lookups refresh the templates themselves, logging any errors. This returns them instead
+    /// (unless the templates are set to `keep_last_good`).
+    ///
+    /// # Errors
+    /// - Failure to load/parse the templates from the store
+    pub fn refresh_now(&mut self) -> Result<bool, Error> {
+        let version = self.templates.version();
+        // the changed keys are only collected for the subscribers of `watch`
+        #[cfg(feature = "async")]
+        let result = match self.reloads.receiver_count() {
+            0 => self.templates.refresh(),
+            _ => self.templates.refresh_changes().map(|changes| {
+                if !changes.is_empty() {
+                    // this only fails when every subscriber has gone away since
+                    let _ = self.reloads.send(ReloadEvent { changed: changes });
+                }
+            }),
+        };
+        #[cfg(not(feature = "async"))]
+        let result = self.templates.refresh();
+
+        let result = result.map(|_| self.templates.version() != version);
+        self.stats.refresh(&result);
+        result
+    }
+
+    /// Get a snapshot of the usage counters of this resolver
+    ///
+    /// Every lookup counts as a resolve, either a hit or a miss.
+    pub fn stats(&self) -> ResolverStats {
+        self.stats.snapshot()
+    }
+
+    /// Refreshes the templates from the store
+    ///
+    /// This can be paired with `get`, to hold shared borrows of the templates. See `refresh_now`
+    ///
+    /// # Errors
+    /// - Failure to load/parse the templates from the store
+    pub fn refresh(&mut self) -> Result<(), Error> {
+        self.refresh_now().map(drop)
+    }
+
+    fn refresh_for(&mut self, context: impl std::fmt::Display) -> Result<(), Error> {
+        self.refresh_now().map(drop).map_err(|err| {
+            log::warn!(
+                "Cannot refresh templates from {} ({}): {}",
+                self.templates.store().name(),
+                context,
+                err
+            );
+            err
+        })
+    }
+
     /// Get a mutable reference to the inner store
     pub fn store_mut(&mut self) -> &mut S {
         self.templates.store_mut()
     }
 
+    /// Compare the currently loaded templates against a `baseline`, such as the shipped defaults
+    ///
+    /// The keys the loaded templates added, removed or changed are relative to the `baseline`.
+    /// This does not refresh the templates.
+    pub fn diff_from_baseline(&self, baseline: &TemplateMap<String>) -> TemplateDiff {
+        baseline.diff_with(self.templates.map())
+    }
+
+    /// Get an owned copy of the currently loaded templates
+    ///
+    /// This does not refresh the templates. The copy can be sent to other threads
+    /// for lookups without touching the resolver.
+    pub fn templates_snapshot(&self) -> TemplateMap<String> {
+        self.templates.map().clone()
+    }
+
+    /// Get the helpers used by `render`
+    #[cfg(feature = "helpers")]
+    pub fn helpers(&self) -> &HelperRegistry {
+        &self.helpers
+    }
+
+    /// Get the helpers used by `render`, e.g. to register more helpers
+    #[cfg(feature = "helpers")]
+    pub fn helpers_mut(&mut self) -> &mut HelperRegistry {
+        self.clear_render_cache();
+        &mut self.helpers
+    }
+
+    /// Convert this into a `ReadOnlyResolver` of the currently loaded templates
+    ///
+    /// This does not refresh the templates, and the store is dropped
+    pub fn into_readonly(self) -> ReadOnlyResolver {
+        let resolver =
+            ReadOnlyResolver::new(self.templates.into_map()).with_empty_policy(self.empty_policy);
+        #[cfg(feature = "helpers")]
+        let resolver = resolver.with_helpers(self.helpers);
+        match self.default_locale {
+            Some(locale) => resolver.with_default_locale(locale),
+            None => resolver,
+        }
+    }
+
     /// Get the templates
     pub fn templates(&self) -> &Templates<S> {
         &self.templates
@@ -195,6 +1041,72 @@ impl<S: TemplateStore> Resolver<S> {
     pub fn templates_mut(&mut self) -> &mut Templates<S> {
         &mut self.templates
     }
+
+    /// Consume this resolver, returning the templates (and with them, the store)
+    ///
+    /// This does not refresh the templates. See `Templates::into_inner` and `Templates::into_parts`
+    pub fn into_templates(self) -> Templates<S> {
+        self.templates
+    }
+}
+
+/// An empty resolver, backed by a `NullStore`
+///
+/// Templates can be added with `insert`, and a store can be attached later with `attach`
+impl Default for Resolver<NullStore> {
+    fn default() -> Self {
+        Self {
+            templates: Templates::default(),
+            default_locale: None,
+            empty_policy: EmptyPolicy::Allow,
+            #[cfg(feature = "async")]
+            reloads: watch::channel(),
+            #[cfg(feature = "helpers")]
+            helpers: HelperRegistry::new(),
+            #[cfg(feature = "lru")]
+            cache: None,
+            stats: stats::Counters::default(),
+        }
+    }
+}
+
+// renders with the same lenient options as the derive
+fn render_str(template: &str, args: markings::Args<'_>) -> Option<String> {
+    // templates without any `${..}` are used as they are
+    if !template.contains("${") {
+        return Some(template.to_string());
+    }
+    let opts = markings::Opts::default()
+        .optional_keys()
+        .duplicate_keys()
+        .empty_template()
+        .build();
+    markings::Template::parse(template, opts)
+        .ok()?
+        .apply(&args)
+        .ok()
+}
+
+// the lookup for `Resolver::resolve_localized`
+fn resolve_localized<'a, S: TemplateStore>(
+    templates: &'a Templates<S>,
+    default_locale: Option<&str>,
+    empty_policy: EmptyPolicy,
+    locale: &str,
+    namespace: &str,
+    variant: &str,
+) -> Option<&'a String> {
+    let candidates = [
+        Some(format!("{}.{}", locale, namespace)),
+        default_locale.map(|default| format!("{}.{}", default, namespace)),
+        Some(namespace.to_string()),
+    ];
+
+    candidates
+        .iter()
+        .flatten()
+        .filter_map(|namespace| templates.get(namespace.as_str())?.get(variant))
+        .find(|template| empty_policy.allows(template))
 }
 
 /// Simple constructor for creating a `PartialStore` from two `MemoryStore`s
@@ -218,3 +1130,106 @@ pub fn partial_file_store(
     let partial = FileStore::new(partial.into(), loader)?;
     Ok(PartialStore::new(default, partial))
 }
+
+/// Simple constructor for creating a `PartialStore` of local overrides over a `base` store
+///
+/// This crate doesn't have an HTTP store (or an HTTP client), so rather than taking a base URL this
+/// takes any store for the defaults, e.g. a `FnStore` that fetches them with the client you already use.
+/// The overrides file doesn't have to exist, see `FileStore::new_lazy`.
+///
+/// # Example
+/// ```rust,no_run
+/// # use template::{FnStore, Templates};
+/// # fn fetch(url: &str) -> Result<String, template::Error> { unimplemented!() }
+/// let base = FnStore::once(|| template::load_lines(&fetch("https://example.com/templates")?));
+/// let store = template::partial_override_store(base, "overrides.txt", template::load_lines);
+/// let templates = Templates::new(store).unwrap();
+/// ```
+pub fn partial_override_store<D: TemplateStore>(
+    base: D,
+    overrides: impl Into<std::path::PathBuf>,
+    loader: LoadFunction,
+) -> PartialStore<D, FileStore> {
+    PartialStore::new(base, FileStore::new_lazy(overrides.into(), loader))
+}
+
+/// Splits a `namespace.variant` key into its namespace and variant
+///
+/// The namespace is everything before the *last* `.`, so it can contain dots. Both parts must be non-empty.
+///
+/// # Example
+/// ```rust
+/// assert_eq!(template::parse_key("response.hello"), Some(("response", "hello")));
+/// assert_eq!(template::parse_key("en.response.hello"), Some(("en.response", "hello")));
+/// assert_eq!(template::parse_key("response."), None);
+/// ```
+pub fn parse_key(key: &str) -> Option<(&str, &str)> {
+    parse_key_with(key, ".")
+}
+
+/// Splits a key into its namespace and variant at the *last* `separator`, see `parse_key`
+///
+/// # Example
+/// ```rust
+/// assert_eq!(template::parse_key_with("en::response::hello", "::"), Some(("en::response", "hello")));
+/// assert_eq!(template::parse_key_with("response.hello", "/"), None);
+/// ```
+pub fn parse_key_with<'a>(key: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    key.rsplit_once(separator)
+        .filter(|(namespace, variant)| !namespace.is_empty() && !variant.is_empty())
+}
+
+/// Returns the namespaces in `map` that aren't in `expected`
+///
+/// This can be used after loading to find typo'd or unused namespaces.
+pub fn validate_namespaces(map: &TemplateMap<String>, expected: &[&str]) -> Vec<String> {
+    let mut unexpected = map
+        .keys()
+        .filter(|key| !expected.contains(&key.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    unexpected.sort();
+    unexpected
+}
+
+/// Returns the `namespace.variant` keys that `T` declares, but that are missing from `templates`
+///
+/// This can be used at startup to find the variants that would never render. The keys are joined with `T::separator`.
+pub fn missing_templates<T: Template>(templates: &Templates<impl TemplateStore>) -> Vec<String> {
+    let namespace = T::namespace(T::declared_casing());
+    let separator = T::separator();
+    let mapping = templates.get(namespace);
+    T::variants(T::declared_casing())
+        .iter()
+        .filter(|variant| mapping.and_then(|m| m.get(**variant)).is_none())
+        .map(|variant| [namespace, separator, variant].concat())
+        .collect()
+}
+
+// the resolvers and built-in stores can be shared across threads
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Error>();
+    assert_send_sync::<TemplateMap<String>>();
+    assert_send_sync::<Templates<FileStore>>();
+    assert_send_sync::<Resolver<FileStore>>();
+    assert_send_sync::<Resolver<MemoryStore>>();
+    assert_send_sync::<Resolver<StaticStore>>();
+    assert_send_sync::<Resolver<StdinStore>>();
+    assert_send_sync::<Resolver<NullStore>>();
+    assert_send_sync::<ReadOnlyResolver>();
+    assert_send_sync::<Resolver<PartialStore<FileStore, FileStore>>>();
+    assert_send_sync::<Resolver<MergedStore<FileStore>>>();
+    assert_send_sync::<Resolver<MapKeysStore<FileStore>>>();
+    assert_send_sync::<Resolver<LimitedStore<FileStore>>>();
+    assert_send_sync::<Resolver<RetryStore<FileStore>>>();
+    assert_send_sync::<Resolver<FnStore<fn() -> Result<TemplateMap<String>, Error>>>>();
+    #[cfg(feature = "env-interp")]
+    assert_send_sync::<Resolver<EnvStore<FileStore>>>();
+    #[cfg(feature = "figment")]
+    assert_send_sync::<Resolver<FigmentStore>>();
+    #[cfg(feature = "include_dir")]
+    assert_send_sync::<Resolver<IncludeDirStore>>();
+    #[cfg(feature = "zip")]
+    assert_send_sync::<Resolver<ArchiveStore>>();
+};
@@ -1,25 +1,165 @@
-#[allow(unused_imports)]
-use crate::{Error, TemplateMap};
+use crate::{Error, Mapping, TemplateMap};
+
+#[cfg(all(
+    feature = "entries",
+    any(feature = "json", feature = "toml", feature = "yaml")
+))]
+use crate::TemplateEntry;
 
 /// Load the `TemplateMap` from a specific format from this string
+///
+/// The built-in loaders ignore a leading UTF-8 byte order mark
 pub type LoadFunction = fn(&str) -> Result<TemplateMap<String>, Error>;
 
+/// Load the `TemplateMap` from a specific binary format from these bytes
+pub type LoadBytesFunction = fn(&[u8]) -> Result<TemplateMap<String>, Error>;
+
 #[cfg(feature = "json")]
 /// Attempts to deserialize a `TemplateMap` from this JSON string
 ///
 /// # Errors
 /// - A JSON deserialize error
 pub fn load_json(input: &str) -> Result<TemplateMap<String>, Error> {
-    serde_json::from_str(input).map_err(deser_err)
+    let input = strip_bom(input);
+    serde_json::from_str(input).map_err(Error::from)
 }
 
 #[cfg(feature = "toml")]
-/// Attempts to deserialize a `TemplateMap` from this TOML string
+/**
+Attempts to deserialize a `TemplateMap` from this TOML string
+
+Each namespace can either be a table of `variant = template`, or an array of tables with `key` and `value` fields:
+
+```toml
+[response]
+hello = "hello ${name}!"
+
+[[errors]]
+key   = "not_found"
+value = "${id} was not found"
+```
+
+# Errors
+- A TOML deserialize error
+*/
+pub fn load_toml(input: &str) -> Result<TemplateMap<String>, Error> {
+    let input = strip_bom(input);
+    check_toml_depth(input)?;
+    serde_toml::de::from_str(input).or_else(|err| {
+        // only if it isn't the usual shape, so its errors are the ones reported
+        let namespaces = serde_toml::de::from_str(input).map_err(|_| err)?;
+        Ok(from_toml_namespaces(namespaces))
+    })
+}
+
+#[cfg(feature = "json")]
+/// Attempts to deserialize a `TemplateMap` from this JSON value
+///
+/// # Errors
+/// - A JSON deserialize error
+pub fn from_json_value(value: serde_json::Value) -> Result<TemplateMap<String>, Error> {
+    serde_json::from_value(value).map_err(Error::from)
+}
+
+#[cfg(feature = "toml")]
+/// Attempts to deserialize a `TemplateMap` from this TOML value
+///
+/// This accepts the same shapes as [`load_toml`]
 ///
 /// # Errors
 /// - A TOML deserialize error
-pub fn load_toml(input: &str) -> Result<TemplateMap<String>, Error> {
-    serde_toml::de::from_str(input).map_err(deser_err)
+pub fn from_toml_value(value: serde_toml::Value) -> Result<TemplateMap<String>, Error> {
+    // only a namespace with an array of `{ key, value }` entries needs the untagged form
+    let entries = value
+        .as_table()
+        .is_some_and(|table| table.values().any(serde_toml::Value::is_array));
+    if entries {
+        let namespaces = value.try_into()?;
+        return Ok(from_toml_namespaces(namespaces));
+    }
+    value.try_into().map_err(Error::from)
+}
+
+#[cfg(feature = "yaml")]
+/// Attempts to deserialize a `TemplateMap` from this YAML value
+///
+/// # Errors
+/// - A YAML deserialize error
+pub fn from_yaml_value(value: serde_yaml::Value) -> Result<TemplateMap<String>, Error> {
+    serde_yaml::from_value(value).map_err(Error::from)
+}
+
+#[cfg(feature = "toml")]
+fn from_toml_namespaces(
+    namespaces: std::collections::HashMap<String, TomlNamespace>,
+) -> TemplateMap<String> {
+    namespaces
+        .into_iter()
+        .map(|(namespace, variants)| (namespace, variants.into_mapping()))
+        .collect()
+}
+
+#[cfg(feature = "toml")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum TomlNamespace {
+    Table(Mapping<String>),
+    Array(Vec<TomlEntry>),
+}
+
+#[cfg(feature = "toml")]
+#[derive(serde::Deserialize)]
+struct TomlEntry {
+    key: String,
+    value: String,
+}
+
+#[cfg(feature = "toml")]
+impl TomlNamespace {
+    fn into_mapping(self) -> Mapping<String> {
+        match self {
+            Self::Table(mapping) => mapping,
+            Self::Array(entries) => entries
+                .into_iter()
+                .map(|entry| (entry.key, entry.value))
+                .collect(),
+        }
+    }
+}
+
+// the toml parser recurses on nested arrays/inline tables, so deeply nested input
+// would overflow the stack. this matches the recursion limit serde_json uses.
+#[cfg(feature = "toml")]
+const MAX_TOML_DEPTH: usize = 128;
+
+// a conservative scan for nested brackets outside of strings and comments
+#[cfg(feature = "toml")]
+fn check_toml_depth(input: &str) -> Result<(), Error> {
+    let (mut depth, mut quote) = (0_usize, None);
+    let mut chars = input.chars();
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some(q), ch) if q == ch => quote = None,
+            (Some(..), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(ch),
+            (None, '#') => {
+                chars.by_ref().find(|&ch| ch == '\n');
+            }
+            (None, '[') | (None, '{') => {
+                depth += 1;
+                if depth > MAX_TOML_DEPTH {
+                    let err = format!("nesting exceeds a depth of {}", MAX_TOML_DEPTH);
+                    return Err(Error::Deserialize(err.into()));
+                }
+            }
+            (None, ']') | (None, '}') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Ok(())
 }
 
 #[cfg(feature = "yaml")]
@@ -28,11 +168,395 @@ pub fn load_toml(input: &str) -> Result<TemplateMap<String>, Error> {
 /// # Errors
 /// - A YAML deserialize error
 pub fn load_yaml(input: &str) -> Result<TemplateMap<String>, Error> {
-    serde_yaml::from_str(input).map_err(deser_err)
+    let input = strip_bom(input);
+    serde_yaml::from_str(input).map_err(Error::from)
+}
+
+#[cfg(feature = "msgpack")]
+/// Attempts to deserialize a `TemplateMap` from these MessagePack bytes
+///
+/// This is a `LoadBytesFunction`, see `FileStore::new_binary`
+///
+/// # Errors
+/// - A MessagePack deserialize error
+pub fn load_msgpack(input: &[u8]) -> Result<TemplateMap<String>, Error> {
+    rmp_serde::from_slice(input).map_err(Error::from)
+}
+
+/**
+Attempts to parse a `TemplateMap` from this simple line-based format
+
+This doesn't require any of the format features.
+
+# Format
+- each line is `namespace.variant = template`
+- the namespace is everything before the *last* `.` in the key, so it can contain dots (e.g. `en.response.hello`)
+- the template can be wrapped in `"` to keep its leading/trailing whitespace, in which `\"` and `\\` are escapes
+- a multi-line template is written as `<<TAG`, followed by its lines, ending with a line of just `TAG`.
+  The lines are kept exactly as they are, without the final newline
+- blank lines and lines starting with `#` are ignored
+
+```text
+# responses
+response.hello       = hello ${name}!
+response.count_items = count is: ${count}
+response.padded      = "  okay  "
+response.help        = <<END
+usage:
+    help ${command}
+END
+```
+
+# Errors
+- A line that isn't a comment or of the form `namespace.variant = template`
+- A multi-line template without its ending `TAG` line
+*/
+pub fn load_lines(input: &str) -> Result<TemplateMap<String>, Error> {
+    parse_lines(input, false)
+}
+
+/// Attempts to parse a `TemplateMap` from the format of [`load_lines`], rejecting duplicate keys
+///
+/// # Errors
+/// - Any error from [`load_lines`]
+/// - A `namespace.variant` key that was already used
+pub fn load_lines_strict(input: &str) -> Result<TemplateMap<String>, Error> {
+    parse_lines(input, true)
+}
+
+fn parse_lines(input: &str, strict: bool) -> Result<TemplateMap<String>, Error> {
+    let input = strip_bom(input);
+    let mut map = TemplateMap::default();
+    let mut lines = input.lines().enumerate();
+    while let Some((i, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let err = |msg: &str| Error::Deserialize(format!("line {}: {}", i + 1, msg).into());
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| err("expected `namespace.variant = template`"))?;
+
+        let (namespace, variant) = crate::parse_key(key.trim())
+            .ok_or_else(|| err("expected a key of `namespace.variant`"))?;
+
+        let value = value.trim();
+        let template = match (value.strip_prefix('"'), value.strip_prefix("<<")) {
+            (Some(quoted), _) => {
+                unquote(quoted).ok_or_else(|| err("unterminated quoted template"))?
+            }
+            (_, Some(tag)) if !tag.trim().is_empty() => {
+                let tag = tag.trim();
+                let mut block = vec![];
+                loop {
+                    match lines.next() {
+                        Some((_, line)) if line.trim() == tag => break,
+                        Some((_, line)) => block.push(line),
+                        None => {
+                            return Err(err(&format!("unterminated block, expected `{}`", tag)))
+                        }
+                    }
+                }
+                block.join("\n")
+            }
+            _ => value.to_string(),
+        };
+
+        let previous = map
+            .entry(namespace.to_string())
+            .or_insert_with(Mapping::default)
+            .insert(variant.to_string(), template);
+        if strict && previous.is_some() {
+            return Err(err(&format!("duplicate key `{}.{}`", namespace, variant)));
+        }
+    }
+    Ok(map)
+}
+
+// this expects the opening quote to have been removed
+fn unquote(input: &str) -> Option<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next()? {
+                ch @ '"' | ch @ '\\' => output.push(ch),
+                ch => {
+                    output.push('\\');
+                    output.push(ch);
+                }
+            },
+            '"' if chars.as_str().trim().is_empty() => return Some(output),
+            ch => output.push(ch),
+        }
+    }
+    None
+}
+
+#[cfg(all(feature = "json", feature = "entries"))]
+/// Attempts to deserialize a `TemplateMap` of `TemplateEntry`s from this JSON string
+///
+/// # Errors
+/// - A JSON deserialize error
+pub fn load_json_entries(input: &str) -> Result<TemplateMap<String, TemplateEntry>, Error> {
+    let input = strip_bom(input);
+    serde_json::from_str(input).map_err(Error::from)
+}
+
+#[cfg(all(feature = "toml", feature = "entries"))]
+/// Attempts to deserialize a `TemplateMap` of `TemplateEntry`s from this TOML string
+///
+/// # Errors
+/// - A TOML deserialize error
+pub fn load_toml_entries(input: &str) -> Result<TemplateMap<String, TemplateEntry>, Error> {
+    let input = strip_bom(input);
+    check_toml_depth(input)?;
+    serde_toml::de::from_str(input).map_err(Error::from)
+}
+
+#[cfg(all(feature = "yaml", feature = "entries"))]
+/// Attempts to deserialize a `TemplateMap` of `TemplateEntry`s from this YAML string
+///
+/// # Errors
+/// - A YAML deserialize error
+pub fn load_yaml_entries(input: &str) -> Result<TemplateMap<String, TemplateEntry>, Error> {
+    let input = strip_bom(input);
+    serde_yaml::from_str(input).map_err(Error::from)
+}
+
+#[cfg(feature = "json")]
+/// Attempts to deserialize a `TemplateMap` from this JSON string, rejecting inputs larger than `max_bytes`
+///
+/// # Errors
+/// - The input was larger than `max_bytes`
+/// - A JSON deserialize error
+pub fn load_json_limited(input: &str, max_bytes: usize) -> Result<TemplateMap<String>, Error> {
+    check_limit(input, max_bytes).and_then(load_json)
+}
+
+#[cfg(feature = "toml")]
+/// Attempts to deserialize a `TemplateMap` from this TOML string, rejecting inputs larger than `max_bytes`
+///
+/// # Errors
+/// - The input was larger than `max_bytes`
+/// - A TOML deserialize error
+pub fn load_toml_limited(input: &str, max_bytes: usize) -> Result<TemplateMap<String>, Error> {
+    check_limit(input, max_bytes).and_then(load_toml)
+}
+
+#[cfg(feature = "yaml")]
+/// Attempts to deserialize a `TemplateMap` from this YAML string, rejecting inputs larger than `max_bytes`
+///
+/// # Errors
+/// - The input was larger than `max_bytes`
+/// - A YAML deserialize error
+pub fn load_yaml_limited(input: &str, max_bytes: usize) -> Result<TemplateMap<String>, Error> {
+    check_limit(input, max_bytes).and_then(load_yaml)
+}
+
+#[cfg(feature = "json")]
+/// Attempts to deserialize a `TemplateMap` from this JSON string, rejecting duplicate keys
+///
+/// The JSON parser keeps the last of any duplicate keys, this reports them instead.
+///
+/// # Errors
+/// - A JSON deserialize error
+/// - A duplicate namespace, or a duplicate variant within a namespace
+pub fn load_json_strict(input: &str) -> Result<TemplateMap<String>, Error> {
+    let input = strip_bom(input);
+    serde_json::from_str(input)
+        .map(|StrictMap(map)| map)
+        .map_err(Error::from)
+}
+
+#[cfg(feature = "toml")]
+/// Attempts to deserialize a `TemplateMap` from this TOML string, rejecting duplicate keys
+///
+/// This accepts the same shapes as [`load_toml`]
+///
+/// # Errors
+/// - A TOML deserialize error
+/// - A duplicate variant within a namespace
+pub fn load_toml_strict(input: &str) -> Result<TemplateMap<String>, Error> {
+    let input = strip_bom(input);
+    check_toml_depth(input)?;
+    serde_toml::de::from_str(input)
+        .map(|StrictTomlMap(map)| map)
+        .map_err(Error::from)
+}
+
+#[cfg(feature = "yaml")]
+/// Attempts to deserialize a `TemplateMap` from this YAML string, rejecting duplicate keys
+///
+/// # Errors
+/// - A YAML deserialize error
+/// - A duplicate namespace, or a duplicate variant within a namespace
+pub fn load_yaml_strict(input: &str) -> Result<TemplateMap<String>, Error> {
+    let input = strip_bom(input);
+    serde_yaml::from_str(input)
+        .map(|StrictMap(map)| map)
+        .map_err(Error::from)
+}
+
+// a template map that fails to deserialize when a key is repeated, rather than keeping the last one
+#[cfg(any(feature = "json", feature = "yaml"))]
+struct StrictMap(TemplateMap<String>);
+
+#[cfg(any(feature = "json", feature = "yaml"))]
+impl<'de> serde::Deserialize<'de> for StrictMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_map(StrictNamespaces { entries: false })
+            .map(Self)
+    }
+}
+
+// this also allows a namespace to be an array of `key` and `value` tables, like `load_toml`
+#[cfg(feature = "toml")]
+struct StrictTomlMap(TemplateMap<String>);
+
+#[cfg(feature = "toml")]
+impl<'de> serde::Deserialize<'de> for StrictTomlMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_map(StrictNamespaces { entries: true })
+            .map(Self)
+    }
+}
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+struct StrictNamespaces {
+    entries: bool,
+}
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+impl<'de> serde::de::Visitor<'de> for StrictNamespaces {
+    type Value = TemplateMap<String>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a map of namespaces")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(
+        self,
+        mut access: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut map = TemplateMap::default();
+        while let Some(namespace) = access.next_key::<String>()? {
+            if map.contains_key(&namespace) {
+                let err = format!("duplicate namespace `{}`", namespace);
+                return Err(serde::de::Error::custom(err));
+            }
+            let variants = access.next_value_seed(StrictVariants {
+                namespace: &namespace,
+                entries: self.entries,
+            })?;
+            map.insert(namespace, variants);
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+struct StrictVariants<'a> {
+    namespace: &'a str,
+    entries: bool,
+}
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+impl StrictVariants<'_> {
+    fn insert<E: serde::de::Error>(
+        &self,
+        mapping: &mut Mapping<String>,
+        variant: String,
+        template: String,
+    ) -> Result<(), E> {
+        if mapping.contains_key(&variant) {
+            let err = format!("duplicate key `{}.{}`", self.namespace, variant);
+            return Err(E::custom(err));
+        }
+        mapping.insert(variant, template);
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+impl<'de> serde::de::DeserializeSeed<'de> for StrictVariants<'_> {
+    type Value = Mapping<String>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        if self.entries {
+            deserializer.deserialize_any(self)
+        } else {
+            deserializer.deserialize_map(self)
+        }
+    }
+}
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "toml"))]
+impl<'de> serde::de::Visitor<'de> for StrictVariants<'_> {
+    type Value = Mapping<String>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a map of variants to templates")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(
+        self,
+        mut access: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut mapping = Mapping::default();
+        while let Some((variant, template)) = access.next_entry()? {
+            self.insert(&mut mapping, variant, template)?;
+        }
+        Ok(mapping)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(
+        self,
+        mut access: A,
+    ) -> Result<Self::Value, A::Error> {
+        #[derive(serde::Deserialize)]
+        struct Entry {
+            key: String,
+            value: String,
+        }
+
+        if !self.entries {
+            return Err(serde::de::Error::invalid_type(
+                serde::de::Unexpected::Seq,
+                &self,
+            ));
+        }
+
+        let mut mapping = Mapping::default();
+        while let Some(Entry { key, value }) = access.next_element()? {
+            self.insert(&mut mapping, key, value)?;
+        }
+        Ok(mapping)
+    }
+}
+
+// some editors start utf-8 files with a byte order mark, which none of the formats allow
+fn strip_bom(input: &str) -> &str {
+    input.strip_prefix('\u{feff}').unwrap_or(input)
 }
 
 #[allow(dead_code)]
-#[cold]
-fn deser_err(err: impl std::error::Error + Sync + Send + 'static) -> Error {
-    Error::Deserialize(Box::new(err))
+fn check_limit(input: &str, max_bytes: usize) -> Result<&str, Error> {
+    if input.len() > max_bytes {
+        let err = format!(
+            "input is {} bytes, limit is {} bytes",
+            input.len(),
+            max_bytes
+        );
+        return Err(Error::Deserialize(err.into()));
+    }
+    Ok(input)
 }
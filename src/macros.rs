@@ -0,0 +1,29 @@
+/**
+Applies the arguments to a one-off template, without defining a `Template` type
+
+This uses the same lenient options as the derive: keys are optional, duplicate keys
+are allowed and templates without any keys are allowed.
+
+This evaluates to an `Option<String>`, which is `None` if the template couldn't be parsed.
+
+# Example
+```rust
+let name = "world";
+let output = template::render!("hello ${name}, ${count} times", name = name, count = 42);
+assert_eq!(output.unwrap(), "hello world, 42 times");
+```
+*/
+#[macro_export]
+macro_rules! render {
+    ($template:expr $(, $key:ident = $value:expr)* $(,)?) => {{
+        let args = $crate::markings::Args::new()$(.with(stringify!($key), &$value))*;
+        let opts = $crate::markings::Opts::default()
+            .optional_keys()
+            .duplicate_keys()
+            .empty_template()
+            .build();
+        $crate::markings::Template::parse($template, opts)
+            .ok()
+            .and_then(|template| template.apply(&args).ok())
+    }};
+}
@@ -1,18 +1,276 @@
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::{fmt::Display, hash::Hash};
 
 /// A mapping of Keys to Values
-#[derive(Debug, Default, serde::Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct Mapping<T: Hash + Eq + Sized, V = T>(HashMap<T, V>);
 
-impl<T: Hash + Eq> Mapping<T> {
+impl<T: Hash + Eq, V> Mapping<T, V> {
     /// Tries to get the value for the key
-    pub fn get<K: ?Sized>(&self, key: &K) -> Option<&T>
+    pub fn get<K>(&self, key: &K) -> Option<&V>
     where
-        K: Hash + Eq + Display,
+        K: ?Sized + Hash + Eq + Display,
         T: Borrow<K>,
     {
         self.0.get(key)
     }
+
+    /// Gets the value for the key, or `default` if it was missing
+    pub fn get_or<'a, K>(&'a self, key: &K, default: &'a V) -> &'a V
+    where
+        K: ?Sized + Hash + Eq + Display,
+        T: Borrow<K>,
+    {
+        self.0.get(key).unwrap_or(default)
+    }
+
+    /// Returns whether the mapping has a value for the key
+    pub fn contains_key<K>(&self, key: &K) -> bool
+    where
+        K: ?Sized + Hash + Eq + Display,
+        T: Borrow<K>,
+    {
+        self.0.contains_key(key)
+    }
+
+    /// Returns the number of entries in the mapping
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether the mapping is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Inserts the value for the key, returning the previous value
+    pub fn insert(&mut self, key: T, value: V) -> Option<V> {
+        self.0.insert(key, value)
+    }
+
+    /// Gets the value for the key, inserting the value produced by `f` if it was missing
+    pub fn get_or_insert_with(&mut self, key: T, f: impl FnOnce() -> V) -> &mut V {
+        self.0.entry(key).or_insert_with(f)
+    }
+
+    /// Returns a view of the mapping, sorted by key
+    pub fn sorted(&self) -> BTreeMap<&T, &V>
+    where
+        T: Ord,
+    {
+        self.0.iter().collect()
+    }
+}
+
+impl<T: Hash + Eq, V> From<HashMap<T, V>> for Mapping<T, V> {
+    fn from(map: HashMap<T, V>) -> Self {
+        Self(map)
+    }
+}
+
+impl<T: Hash + Eq, V> std::iter::FromIterator<(T, V)> for Mapping<T, V> {
+    fn from_iter<I: IntoIterator<Item = (T, V)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<T: Hash + Eq, V> IntoIterator for Mapping<T, V> {
+    type Item = (T, V);
+    type IntoIter = std::collections::hash_map::IntoIter<T, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/**
+A template mapping of `T` to `Mapping<T, V>`
+
+This is a map of `namespace` to its `variant` to `template` mapping.
+
+It dereferences to the inner `HashMap`, so all of the usual map methods are available.
+
+# Example
+```rust
+# #[cfg(feature = "json")] {
+let input = r#"{"response":{"hello":"hello ${name}!"}}"#;
+let map = template::load_json(input).unwrap();
+assert_eq!(map["response"].get("hello").unwrap(), "hello ${name}!");
+
+// it serializes to the same shape it was deserialized from
+let output = serde_json::to_string(&map).unwrap();
+assert_eq!(output, input);
+# }
+```
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct TemplateMap<T: Hash + Eq, V = T>(HashMap<T, Mapping<T, V>>);
+
+impl<T: Hash + Eq, V> TemplateMap<T, V> {
+    /// Create a new, empty `TemplateMap`
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    /// Consume this, returning the inner `HashMap`
+    pub fn into_inner(self) -> HashMap<T, Mapping<T, V>> {
+        self.0
+    }
+
+    /// Returns a view of the map, sorted by namespace and then by variant
+    ///
+    /// Iterating (or serializing) this view has a stable order, which is useful for diffs and golden tests.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(feature = "json")] {
+    /// let input = r#"{"b":{"z":"1","y":"2"},"a":{"x":"3"}}"#;
+    /// let map = template::load_json(input).unwrap();
+    /// let output = serde_json::to_string(&map.sorted()).unwrap();
+    /// assert_eq!(output, r#"{"a":{"x":"3"},"b":{"y":"2","z":"1"}}"#);
+    /// # }
+    /// ```
+    pub fn sorted(&self) -> BTreeMap<&T, BTreeMap<&T, &V>>
+    where
+        T: Ord,
+    {
+        self.0
+            .iter()
+            .map(|(namespace, mapping)| (namespace, mapping.sorted()))
+            .collect()
+    }
+
+    /// Returns the sorted `namespace.variant` keys that were added, removed or changed between this map and `other`
+    pub fn diff(&self, other: &Self) -> Vec<String>
+    where
+        T: Display,
+        V: PartialEq,
+    {
+        let TemplateDiff {
+            added,
+            removed,
+            changed,
+        } = self.diff_with(other);
+        let mut keys = [added, removed, changed].concat();
+        keys.sort();
+        keys
+    }
+
+    /// Returns the `namespace.variant` keys that `other` added, removed or changed, compared to this map
+    pub fn diff_with(&self, other: &Self) -> TemplateDiff
+    where
+        T: Display,
+        V: PartialEq,
+    {
+        // only the keys that differ are formatted
+        let key = |namespace: &T, variant: &T| format!("{}.{}", namespace, variant);
+
+        let mut diff = TemplateDiff::default();
+        for (namespace, mapping) in &self.0 {
+            let other = other.0.get(namespace);
+            for (variant, value) in &mapping.0 {
+                match other.and_then(|mapping| mapping.0.get(variant)) {
+                    None => diff.removed.push(key(namespace, variant)),
+                    Some(other) if other != value => diff.changed.push(key(namespace, variant)),
+                    Some(..) => {}
+                }
+            }
+        }
+        for (namespace, mapping) in &other.0 {
+            let this = self.0.get(namespace);
+            for variant in mapping.0.keys() {
+                if this.is_none_or(|mapping| !mapping.0.contains_key(variant)) {
+                    diff.added.push(key(namespace, variant));
+                }
+            }
+        }
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort();
+        diff
+    }
+}
+
+/// The sorted `namespace.variant` keys that differ between two `TemplateMap`s, see `TemplateMap::diff_with`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TemplateDiff {
+    /// The keys that are only in the other map
+    pub added: Vec<String>,
+    /// The keys that are missing from the other map
+    pub removed: Vec<String>,
+    /// The keys whose templates are different in the other map
+    pub changed: Vec<String>,
+}
+
+impl TemplateDiff {
+    /// Returns whether the maps were the same
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Returns how many keys differ
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+}
+
+impl<T: Hash + Eq, V> std::ops::Deref for TemplateMap<T, V> {
+    type Target = HashMap<T, Mapping<T, V>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Hash + Eq, V> std::ops::DerefMut for TemplateMap<T, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: Hash + Eq, V> From<HashMap<T, Mapping<T, V>>> for TemplateMap<T, V> {
+    fn from(map: HashMap<T, Mapping<T, V>>) -> Self {
+        Self(map)
+    }
+}
+
+impl<T: Hash + Eq, V> std::iter::FromIterator<(T, Mapping<T, V>)> for TemplateMap<T, V> {
+    fn from_iter<I: IntoIterator<Item = (T, Mapping<T, V>)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<T: Hash + Eq, V> Extend<(T, Mapping<T, V>)> for TemplateMap<T, V> {
+    fn extend<I: IntoIterator<Item = (T, Mapping<T, V>)>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<T: Hash + Eq, V> IntoIterator for TemplateMap<T, V> {
+    type Item = (T, Mapping<T, V>);
+    type IntoIter = std::collections::hash_map::IntoIter<T, Mapping<T, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T: Hash + Eq, V> IntoIterator for &'a TemplateMap<T, V> {
+    type Item = (&'a T, &'a Mapping<T, V>);
+    type IntoIter = std::collections::hash_map::Iter<'a, T, Mapping<T, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a, T: Hash + Eq, V> IntoIterator for &'a mut TemplateMap<T, V> {
+    type Item = (&'a T, &'a mut Mapping<T, V>);
+    type IntoIter = std::collections::hash_map::IterMut<'a, T, Mapping<T, V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
 }
@@ -0,0 +1,115 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A pool of reusable `String` buffers for rendering, see `Template::apply_pooled`
+///
+/// The pool can be shared across threads. Buffers are returned to the pool when
+/// the `PooledString` is dropped, keeping at most `capacity` of them.
+///
+/// # Example
+/// ```rust
+/// # use template::{NameCasing, RenderPool, Template};
+/// # struct Okay;
+/// # impl Template for Okay {
+/// #     fn namespace(_: NameCasing) -> &'static str { "response" }
+/// #     fn name(_: NameCasing) -> &'static str { "response" }
+/// #     fn variant(&self, _: NameCasing) -> &'static str { "okay" }
+/// #     fn apply(&self, input: &str) -> Option<String> { Some(input.to_string()) }
+/// # }
+/// let pool = RenderPool::new(16);
+/// for _ in 0..3 {
+///     let output = Okay.apply_pooled("okay response", &pool).unwrap();
+///     assert_eq!(*output, "okay response");
+/// }
+/// assert_eq!(pool.available(), 1);
+/// ```
+#[derive(Debug)]
+pub struct RenderPool {
+    buffers: Mutex<Vec<String>>,
+    capacity: usize,
+}
+
+impl RenderPool {
+    /// Create a new, empty pool which keeps at most `capacity` buffers
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffers: Mutex::new(Vec::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Take an empty buffer from the pool, allocating a new one if the pool is empty
+    pub fn take(&self) -> PooledString<'_> {
+        let buf = self.buffers().pop().unwrap_or_default();
+        PooledString { buf, pool: self }
+    }
+
+    /// How many buffers are available in the pool
+    pub fn available(&self) -> usize {
+        self.buffers().len()
+    }
+
+    /// The maximum number of buffers kept by the pool
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn release(&self, mut buf: String) {
+        let mut buffers = self.buffers();
+        if buf.capacity() > 0 && buffers.len() < self.capacity {
+            buf.clear();
+            buffers.push(buf);
+        }
+    }
+
+    // a panic while holding the lock can't leave the free list in a bad state
+    fn buffers(&self) -> MutexGuard<'_, Vec<String>> {
+        self.buffers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for RenderPool {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+/// A `String` buffer borrowed from a `RenderPool`
+///
+/// It is returned to the pool when dropped, use `into_string` to keep it instead
+#[derive(Debug)]
+pub struct PooledString<'a> {
+    buf: String,
+    pool: &'a RenderPool,
+}
+
+impl PooledString<'_> {
+    /// Take the `String` out of this buffer, so it isn't returned to the pool
+    pub fn into_string(mut self) -> String {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+impl std::ops::Deref for PooledString<'_> {
+    type Target = String;
+    fn deref(&self) -> &Self::Target {
+        &self.buf
+    }
+}
+
+impl std::ops::DerefMut for PooledString<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buf
+    }
+}
+
+impl std::fmt::Display for PooledString<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.buf)
+    }
+}
+
+impl Drop for PooledString<'_> {
+    fn drop(&mut self) {
+        self.pool.release(std::mem::take(&mut self.buf));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{EmptyPolicy, Error, Fallback, NullStore, Template, TemplateMap, Templates};
+
+#[cfg(feature = "helpers")]
+use crate::HelperRegistry;
+
+/// A resolver for a fixed set of templates, which never refreshes
+///
+/// All of the lookups only need `&self`. See `Resolver::into_readonly`
+#[derive(Debug)]
+pub struct ReadOnlyResolver {
+    templates: Templates<NullStore>,
+    default_locale: Option<String>,
+    empty_policy: EmptyPolicy,
+    #[cfg(feature = "helpers")]
+    helpers: HelperRegistry,
+}
+
+impl ReadOnlyResolver {
+    /// Create a new resolver for these `templates`
+    pub fn new(templates: TemplateMap<String>) -> Self {
+        Self {
+            templates: templates.into(),
+            default_locale: None,
+            empty_policy: EmptyPolicy::Allow,
+            #[cfg(feature = "helpers")]
+            helpers: HelperRegistry::new(),
+        }
+    }
+
+    /// Tries to get the template string for `namespace.variant`
+    pub fn resolve(&self, namespace: impl AsRef<str>, variant: impl AsRef<str>) -> Option<&String> {
+        self.templates
+            .get(namespace.as_ref())?
+            .get(variant.as_ref())
+            .filter(|template| self.empty_policy.allows(template))
+    }
+
+    /// Tries to get the template string for a `namespace.variant` key, see `parse_key`
+    pub fn resolve_key(&self, key: &str) -> Option<&String> {
+        let (namespace, variant) = crate::parse_key(key)?;
+        self.resolve(namespace, variant)
+    }
+
+    /// Gets the template string for `namespace.variant`, or `default` if it is missing
+    pub fn resolve_with_default<'a>(
+        &'a self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+        default: &'a str,
+    ) -> &'a str {
+        self.resolve(namespace, variant)
+            .map(String::as_str)
+            .unwrap_or(default)
+    }
+
+    /// Tries to get the template string for `namespace.variant` in a specific `locale`
+    ///
+    /// See `Resolver::resolve_localized`
+    pub fn resolve_localized(
+        &self,
+        locale: impl AsRef<str>,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+    ) -> Option<&String> {
+        crate::resolve_localized(
+            &self.templates,
+            self.default_locale.as_deref(),
+            self.empty_policy,
+            locale.as_ref(),
+            namespace.as_ref(),
+            variant.as_ref(),
+        )
+    }
+
+    /// Tries to apply the template for this `value`, expanding any references to other templates
+    ///
+    /// See `Resolver::apply`
+    ///
+    /// # Errors
+    /// - A referenced template was not found
+    /// - A template references itself, directly or through other templates
+    pub fn apply<T: Template>(&self, value: &T) -> Result<Option<String>, Error> {
+        let casing = value.variant_casing();
+        let namespace = value.variant_namespace(casing);
+        let variant = value.variant(casing);
+        let template = match self.resolve(namespace, variant) {
+            Some(template) => template.as_str(),
+            None => match T::FALLBACK {
+                Some(Fallback::Variant(variant)) => {
+                    match self.resolve(T::namespace(T::declared_casing()), variant) {
+                        Some(template) => template,
+                        None => return Ok(None),
+                    }
+                }
+                Some(Fallback::Template(template)) => template,
+                None => return Ok(None),
+            },
+        };
+
+        let template = self.templates.expand_references(template)?;
+        Ok(value.apply(&template))
+    }
+
+    /// Tries to render the template for `namespace.variant` with these `args`
+    ///
+    /// See `Resolver::render`
+    pub fn render(
+        &self,
+        namespace: impl AsRef<str>,
+        variant: impl AsRef<str>,
+        args: markings::Args<'_>,
+    ) -> Option<String> {
+        let template = self.resolve(namespace, variant)?;
+        #[cfg(feature = "helpers")]
+        let args = self.helpers.bind(template, args);
+        crate::render_str(template, args)
+    }
+
+    /// Set the default locale used as a fallback by `resolve_localized`
+    pub fn with_default_locale(mut self, locale: impl Into<String>) -> Self {
+        self.default_locale.replace(locale.into());
+        self
+    }
+
+    /// Get the default locale used as a fallback by `resolve_localized`
+    pub fn default_locale(&self) -> Option<&str> {
+        self.default_locale.as_deref()
+    }
+
+    /// Set how empty templates are treated by the lookups, see `EmptyPolicy`
+    pub fn with_empty_policy(mut self, policy: EmptyPolicy) -> Self {
+        self.empty_policy = policy;
+        self
+    }
+
+    /// Get how empty templates are treated by the lookups
+    pub fn empty_policy(&self) -> EmptyPolicy {
+        self.empty_policy
+    }
+
+    /// Set the helpers used by `render`
+    #[cfg(feature = "helpers")]
+    pub fn with_helpers(mut self, helpers: HelperRegistry) -> Self {
+        self.helpers = helpers;
+        self
+    }
+
+    /// Get the templates
+    pub fn templates(&self) -> &TemplateMap<String> {
+        self.templates.map()
+    }
+}
+
+impl From<TemplateMap<String>> for ReadOnlyResolver {
+    fn from(templates: TemplateMap<String>) -> Self {
+        Self::new(templates)
+    }
+}
@@ -0,0 +1,133 @@
+use crate::conditional::{self, expand_conditionals};
+use std::{fmt, ops::Range};
+
+/**
+Applies a template string, writing the output to `out`
+
+This is what the derive uses for `Template::apply_fmt`, the values are written straight to `out` without building the output first.
+
+- `condition` is used for the conditional sections, see `expand_conditionals`
+- `bind` writes the value of a `key` to the writer it is given, or returns `None` if the `key` isn't bound
+- keys that aren't bound are left in place, unless `strict` is set, which fails the template instead
+
+This returns `Ok(false)` if the template could not be applied (an unterminated `${..}`, a `{` inside of a key, or unbalanced conditional sections), nothing is written to `out` in that case.
+
+# Errors
+- Any error from writing to `out`
+
+# Example
+```rust
+# use template::apply_fields;
+let input = "hello ${name}!${if loud}!!${endif} ${unknown}";
+let mut out = String::new();
+let applied = apply_fields(input, &mut out, false, |key| key == "loud", |key, out| match key {
+    "name" => Some(out.write_str("world")),
+    _ => None,
+});
+assert!(applied.unwrap());
+assert_eq!(out, "hello world!!! ${unknown}");
+```
+*/
+pub fn apply_fields(
+    input: &str,
+    out: &mut dyn fmt::Write,
+    strict: bool,
+    condition: impl Fn(&str) -> bool,
+    mut bind: impl FnMut(&str, &mut dyn fmt::Write) -> Option<fmt::Result>,
+) -> Result<bool, fmt::Error> {
+    let expanded;
+    let input = if conditional::has_conditionals(input) {
+        expanded = match expand_conditionals(input, condition) {
+            Some(expanded) => expanded,
+            None => return Ok(false),
+        };
+        expanded.as_str()
+    } else {
+        input
+    };
+
+    // check the whole template first, so nothing is written if it can't be applied
+    for key in Keys::new(input) {
+        match key {
+            Some((_, key)) if strict && bind(key, &mut Discard).is_none() => return Ok(false),
+            Some(..) => {}
+            None => return Ok(false),
+        }
+    }
+
+    let mut last = 0;
+    for (span, key) in Keys::new(input).flatten() {
+        out.write_str(&input[last..span.start])?;
+        if bind(key, out).transpose()?.is_none() {
+            out.write_str(&input[span.clone()])?;
+        }
+        last = span.end;
+    }
+    out.write_str(&input[last..])?;
+    Ok(true)
+}
+
+/// The `${key}`s of a template, with their spans
+///
+/// This yields `None` for a `${` without a closing `}`, or with a `{` before it (the same templates `markings` rejects)
+pub(crate) struct Keys<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Keys<'a> {
+    pub(crate) const fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Keys<'a> {
+    type Item = Option<(Range<usize>, &'a str)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let head = self.pos + self.input[self.pos..].find("${")?;
+        let rest = &self.input[head + 2..];
+        match rest.find(['{', '}']) {
+            Some(tail) if rest[tail..].starts_with('}') => {
+                self.pos = head + 2 + tail + 1;
+                Some(Some((head..self.pos, &rest[..tail])))
+            }
+            _ => {
+                self.pos = self.input.len();
+                Some(None)
+            }
+        }
+    }
+}
+
+/// A writer that fails once more than `max_len` bytes would be written to `out`, keeping the part that fits
+pub(crate) struct Limited<'a> {
+    pub(crate) out: &'a mut String,
+    pub(crate) max_len: usize,
+    pub(crate) overflowed: bool,
+}
+
+impl fmt::Write for Limited<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut end = self.max_len.saturating_sub(self.out.len());
+        if s.len() <= end {
+            self.out.push_str(s);
+            return Ok(());
+        }
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.out.push_str(&s[..end]);
+        self.overflowed = true;
+        Err(fmt::Error)
+    }
+}
+
+/// A writer that throws away what's written to it
+struct Discard;
+
+impl fmt::Write for Discard {
+    fn write_str(&mut self, _: &str) -> fmt::Result {
+        Ok(())
+    }
+}
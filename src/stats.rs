@@ -0,0 +1,54 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A snapshot of the usage counters of a `Resolver`
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ResolverStats {
+    /// How many templates were looked up
+    pub resolves: u64,
+    /// How many of the lookups found a template
+    pub hits: u64,
+    /// How many of the lookups didn't find a template
+    pub misses: u64,
+    /// How many times a refresh changed the templates
+    pub refreshes: u64,
+    /// How many times a refresh failed
+    pub refresh_failures: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    refreshes: AtomicU64,
+    refresh_failures: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn lookup(&self, found: bool) {
+        let counter = if found { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn refresh(&self, result: &Result<bool, crate::Error>) {
+        match result {
+            Ok(false) => return,
+            Ok(true) => &self.refreshes,
+            Err(..) => &self.refresh_failures,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> ResolverStats {
+        let (hits, misses) = (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        );
+        ResolverStats {
+            resolves: hits + misses,
+            hits,
+            misses,
+            refreshes: self.refreshes.load(Ordering::Relaxed),
+            refresh_failures: self.refresh_failures.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use crate::{Error, LoadFunction, TemplateMap};
+use crate::{Error, LoadBytesFunction, LoadFunction, Mapping, TemplateMap};
 
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 /// A backing store for a set of templates
 pub trait TemplateStore {
@@ -12,15 +14,99 @@ pub trait TemplateStore {
     /// - Any deserialization error
     // TODO make this return an Result<Status, Error>
     fn parse_map(&mut self) -> Result<TemplateMap<String>, Error>;
-    /// Returns whether the template changed
+    /// Loads the template map for the first time
+    ///
+    /// This is called once by `Templates::new`, rather than `changed` and `parse_map`.
+    /// By default this parses the map
+    ///
+    /// # Errors
+    /// - Any I/O error associated with fetching this data
+    /// - Any deserialization error
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.parse_map()
+    }
+    /// Returns whether the template changed since it was last loaded
     fn changed(&mut self) -> bool;
+    /// Checks whether the store is reachable, without affecting its change detection
+    ///
+    /// This returns whether the store has changed since it was last parsed, if it can tell.
+    /// By default this returns `Ok(false)`
+    ///
+    /// # Errors
+    /// - The store is not reachable
+    fn probe(&self) -> Result<bool, Error> {
+        Ok(false)
+    }
+    /// Returns when the template for `namespace.variant` was last modified, if the store can tell
+    ///
+    /// By default this returns `None`
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        let _ = (namespace, variant);
+        None
+    }
+    /// Returns which layer of the store the template for `namespace.variant` came from
+    ///
+    /// This doesn't check whether the template exists. By default this returns `Source::Store`
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        let _ = (namespace, variant);
+        Source::Store
+    }
+    /// A human-readable name for this store, used in diagnostics
+    ///
+    /// By default this is the store's type name
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(std::any::type_name::<Self>())
+    }
+    /// Returns which namespaces changed, if the store can tell
+    ///
+    /// This is only consulted after `changed` returns true. Returning `None`
+    /// (the default) causes the entire map to be reparsed.
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        None
+    }
+    /// Tries to parse only the namespaces in `keys`
+    ///
+    /// By default this parses the entire map and keeps only the requested namespaces
+    ///
+    /// # Errors
+    /// - Any I/O error associated with fetching this data
+    /// - Any deserialization error
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        let mut map = self.parse_map()?;
+        map.retain(|key, _| keys.contains(key));
+        Ok(map)
+    }
+}
+
+/// Where a template came from, see `TemplateStore::source`
+#[derive(Debug, Copy, Clone, PartialEq, Hash, Eq)]
+#[non_exhaustive]
+pub enum Source {
+    /// The store isn't layered
+    Store,
+    /// The default (base) store of a `PartialStore`
+    Default,
+    /// The partial (override) store of a `PartialStore`
+    Partial,
 }
 
 /// A file-based backing for templates
+///
+/// Changes are detected by the file's modification time. As some filesystems
+/// have a coarse modification time, the file's size (and then a hash of its
+/// content) is compared when the modification time hasn't changed.
 pub struct FileStore {
     file: PathBuf,
     last: Option<SystemTime>,
-    loader: LoadFunction,
+    loader: Loader,
+    lazy: bool,
+    lossy: bool,
+    follow_symlinks: bool,
+    hash_detection: bool,
+    target: Option<PathBuf>,
+    len: Option<u64>,
+    hash: Option<u64>,
+    checked: SystemTime,
 }
 
 impl std::fmt::Debug for FileStore {
@@ -28,53 +114,259 @@ impl std::fmt::Debug for FileStore {
         f.debug_struct("FileStore")
             .field("file", &self.file)
             .field("last", &self.last)
+            .field("lazy", &self.lazy)
+            .field("lossy", &self.lossy)
+            .field("follow_symlinks", &self.follow_symlinks)
+            .field("hash_detection", &self.hash_detection)
             .finish()
     }
 }
 
+#[derive(Copy, Clone)]
+enum Loader {
+    Text(LoadFunction),
+    Bytes(LoadBytesFunction),
+}
+
+// the coarsest modification time resolution of the common filesystems (FAT)
+const COARSE_MTIME_RESOLUTION: Duration = Duration::from_secs(2);
+// filesystems with sub-second modification times still update them on a clock tick
+const FINE_MTIME_RESOLUTION: Duration = Duration::from_millis(50);
+
+fn mtime_resolution(modified: SystemTime) -> Duration {
+    // coarse filesystems only have whole seconds
+    match modified.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(time) if time.subsec_nanos() != 0 => FINE_MTIME_RESOLUTION,
+        _ => COARSE_MTIME_RESOLUTION,
+    }
+}
+
 impl FileStore {
     /// Create a store from this `PathBuf`
     ///
+    /// The file isn't read until the templates are parsed, so a missing file is
+    /// only reported then. See `new_strict` and `new_lazy` for the alternatives.
+    ///
     /// # Errors
-    /// - File wasn't found / not readable
+    /// - This currently does not fail
     pub fn new(file: PathBuf, loader: LoadFunction) -> Result<Self, Error> {
-        Ok(Self {
+        Ok(Self::with_loader(file, Loader::Text(loader), false))
+    }
+
+    /// Create a store from this `PathBuf`, ensuring the file is readable
+    ///
+    /// # Errors
+    /// - File wasn't found / not readable
+    pub fn new_strict(file: PathBuf, loader: LoadFunction) -> Result<Self, Error> {
+        if let Err(err) = std::fs::File::open(&file) {
+            return Err(Error::io_at(file, err));
+        }
+        Self::new(file, loader)
+    }
+
+    /// Create a store from this `PathBuf`, which doesn't have to exist yet
+    ///
+    /// A missing file is treated as an empty set of templates until it is created
+    pub fn new_lazy(file: PathBuf, loader: LoadFunction) -> Self {
+        Self::with_loader(file, Loader::Text(loader), true)
+    }
+
+    /// Create a store from this `PathBuf`, for a binary format
+    ///
+    /// The file's bytes are given to the `loader` as they are, so `with_lossy_utf8` has no effect
+    pub fn new_binary(file: PathBuf, loader: LoadBytesFunction) -> Self {
+        Self::with_loader(file, Loader::Bytes(loader), false)
+    }
+
+    /// Set whether invalid UTF-8 in the file is replaced, rather than failing to load
+    ///
+    /// The invalid sequences are replaced with `U+FFFD` and a warning is logged. This is off by default.
+    pub fn with_lossy_utf8(mut self, lossy: bool) -> Self {
+        self.lossy = lossy;
+        self
+    }
+
+    /// Set whether the file's symlink target is tracked, so repointing the symlink is a change
+    ///
+    /// The modification time is always read from the target, but a new target can be older than
+    /// the previous one. With this set, a change in the resolved target is also a change. This is off by default.
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Set whether a newer modification time is only a change when the file's content differs
+    ///
+    /// Modification times are unreliable on some (networked) filesystems. With this set, the file
+    /// is read and hashed when its modification time is newer, and it is only a change when the
+    /// hash differs from the last loaded one. This is off by default.
+    pub fn with_hash_detection(mut self, hash_detection: bool) -> Self {
+        self.hash_detection = hash_detection;
+        self
+    }
+
+    // this is only true when the loaded (or previously resolved) target was replaced by another one
+    fn target_changed(&mut self) -> bool {
+        let target = std::fs::canonicalize(&self.file).ok();
+        let prev = std::mem::replace(&mut self.target, target);
+        prev.is_some() && self.target.is_some() && prev != self.target
+    }
+
+    const fn with_loader(file: PathBuf, loader: Loader, lazy: bool) -> Self {
+        Self {
             file,
             last: None,
             loader,
-        })
+            lazy,
+            lossy: false,
+            follow_symlinks: false,
+            hash_detection: false,
+            target: None,
+            len: None,
+            hash: None,
+            checked: SystemTime::UNIX_EPOCH,
+        }
     }
+
+    // this is only called when the modification time hasn't changed
+    fn content_changed(&mut self, modified: SystemTime, len: u64) -> bool {
+        if self.len != Some(len) {
+            return true;
+        }
+
+        // any edit after the content was last checked would have a newer modification time
+        if self
+            .checked
+            .duration_since(modified)
+            .is_ok_and(|elapsed| elapsed > mtime_resolution(modified))
+        {
+            return false;
+        }
+
+        let now = SystemTime::now();
+        match std::fs::read(&self.file) {
+            Ok(data) if Some(hash(&data)) != self.hash => true,
+            Ok(..) => {
+                self.checked = now;
+                false
+            }
+            Err(..) => false,
+        }
+    }
+
+    // an unreadable file is left for `parse_map` to report
+    fn hash_changed(&mut self) -> bool {
+        let now = SystemTime::now();
+        match std::fs::read(&self.file) {
+            Ok(data) if Some(hash(&data)) != self.hash => true,
+            Ok(..) => {
+                self.checked = now;
+                false
+            }
+            Err(..) => true,
+        }
+    }
+}
+
+fn hash(data: &[u8]) -> u64 {
+    use std::hash::Hasher as _;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hasher.write(data);
+    hasher.finish()
 }
 
 impl TemplateStore for FileStore {
     fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
-        (self.loader)(&std::fs::read_to_string(&self.file)?)
+        self.checked = SystemTime::now();
+        // this is taken before the read, so an edit during it is seen by the next `changed`
+        let modified = std::fs::metadata(&self.file).and_then(|md| md.modified());
+        if self.follow_symlinks {
+            // the target that was loaded, so the next `changed` sees it being repointed
+            self.target = std::fs::canonicalize(&self.file).ok();
+        }
+        match std::fs::read(&self.file) {
+            Ok(data) => {
+                self.last = modified.ok();
+                self.len.replace(data.len() as u64);
+                self.hash.replace(hash(&data));
+                let loader = match self.loader {
+                    Loader::Text(loader) => loader,
+                    Loader::Bytes(loader) => return loader(&data),
+                };
+                match String::from_utf8(data) {
+                    Ok(data) => loader(&data),
+                    Err(err) if self.lossy => {
+                        log::warn!(
+                            "FileStore file has invalid UTF-8, replacing it: {}",
+                            self.file.display()
+                        );
+                        loader(&String::from_utf8_lossy(err.as_bytes()))
+                    }
+                    Err(err) => {
+                        let err = std::io::Error::new(std::io::ErrorKind::InvalidData, err);
+                        Err(Error::io_at(&self.file, err))
+                    }
+                }
+            }
+            Err(err) if self.lazy && err.kind() == std::io::ErrorKind::NotFound => {
+                log::debug!("FileStore file does not exist yet: {}", self.file.display());
+                self.last.take();
+                self.len.take();
+                self.hash.take();
+                Ok(TemplateMap::default())
+            }
+            Err(err) => Err(Error::io_at(&self.file, err)),
+        }
     }
 
     fn changed(&mut self) -> bool {
-        if self.last.is_none() {
-            log::debug!("FileStore initial changed");
-            self.last.replace(std::time::SystemTime::now());
+        let metadata = std::fs::metadata(&self.file).ok();
+        let modified = metadata.as_ref().and_then(|md| md.modified().ok());
+
+        if self.follow_symlinks && self.target_changed() {
+            log::debug!("FileStore target changed");
+            self.last.replace(modified.unwrap_or_else(SystemTime::now));
             return true;
         }
 
-        // TODO clean this up (this breaks the Option<T: TemplateStore>)
-        match std::fs::metadata(&self.file)
-            .and_then(|md| md.modified())
-            .ok()
-            .filter(|&last| {
-                if let Some(prev) = self.last {
-                    return last > prev;
-                }
-                true
-            }) {
-            Some(time) => {
-                log::debug!("FileStore changed");
+        let (time, len) = match (modified, metadata) {
+            (Some(time), Some(md)) => (time, md.len()),
+            _ => return false,
+        };
+
+        // when there is no previous time, the file wasn't loaded (or didn't exist when it was)
+        if let Some(prev) = self.last {
+            if time < prev || time == prev && !self.content_changed(time, len) {
+                return false;
+            }
+            if time > prev && self.hash_detection && !self.hash_changed() {
+                log::debug!("FileStore touched, but the content is the same");
                 self.last.replace(time);
-                true
+                return false;
             }
-            None => false,
         }
+
+        log::debug!("FileStore changed");
+        self.last.replace(time);
+        true
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        match std::fs::metadata(&self.file).and_then(|md| md.modified()) {
+            Ok(time) => Ok(self.last.is_none_or(|prev| time > prev)),
+            Err(err) if self.lazy && err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(Error::io_at(&self.file, err)),
+        }
+    }
+
+    fn last_modified(&self, _namespace: &str, _variant: &str) -> Option<SystemTime> {
+        std::fs::metadata(&self.file)
+            .and_then(|md| md.modified())
+            .ok()
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.file.to_string_lossy()
     }
 }
 
@@ -87,6 +379,7 @@ impl TemplateStore for FileStore {
 pub struct PartialStore<D, P> {
     default: D,
     partial: P,
+    overridden: HashSet<String>,
 }
 
 impl<D, P> PartialStore<D, P> {
@@ -96,7 +389,11 @@ impl<D, P> PartialStore<D, P> {
         D: TemplateStore,
         P: TemplateStore,
     {
-        Self { default, partial }
+        Self {
+            default,
+            partial,
+            overridden: HashSet::new(),
+        }
     }
 
     /// Get a reference to the efault template store
@@ -125,21 +422,91 @@ impl<D, P> PartialStore<D, P> {
     }
 }
 
-impl<D: TemplateStore, P: TemplateStore> TemplateStore for PartialStore<D, P> {
-    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
-        let left = self.partial.parse_map().unwrap_or_default();
+impl<D: TemplateStore, P: TemplateStore> PartialStore<D, P> {
+    fn merge(
+        &mut self,
+        left: Result<TemplateMap<String>, Error>,
+        right: impl FnOnce(&mut D) -> Result<TemplateMap<String>, Error>,
+    ) -> Result<TemplateMap<String>, Error> {
+        let left = left.unwrap_or_default();
         log::trace!("got: partial entries: {}", left.len());
-        let mut right = self.default.parse_map()?;
+        self.overridden = left.keys().cloned().collect();
+        let mut right = right(&mut self.default)?;
         log::trace!("got: default entries: {}", left.len());
         right.extend(left);
         log::trace!("after merge: total: {}", right.len());
         Ok(right)
     }
+}
+
+impl<D: TemplateStore, P: TemplateStore> TemplateStore for PartialStore<D, P> {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        let left = self.partial.parse_map();
+        self.merge(left, D::parse_map)
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        let left = self.partial.initial_load();
+        self.merge(left, D::initial_load)
+    }
 
     fn changed(&mut self) -> bool {
         // this will only check the partial. the default should never change (while running)
         self.partial.changed()
     }
+
+    fn probe(&self) -> Result<bool, Error> {
+        // the partial store is allowed to be missing
+        self.default.probe()?;
+        Ok(self.partial.probe().unwrap_or_default())
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        // the template comes from the partial only when it replaced the namespace
+        if self.overridden.contains(namespace) {
+            self.partial.last_modified(namespace, variant)
+        } else {
+            self.default.last_modified(namespace, variant)
+        }
+    }
+
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        // the partial replaces entire namespaces
+        if self.overridden.contains(namespace) {
+            return Source::Partial;
+        }
+        match self.default.source(namespace, variant) {
+            Source::Store => Source::Default,
+            source => source,
+        }
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        format!("{} (over {})", self.partial.name(), self.default.name()).into()
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        self.partial.changed_keys()
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        let left = self.partial.parse_keys(keys).unwrap_or_default();
+        // the namespaces the partial doesn't have (anymore) come from the default
+        let missing = keys
+            .iter()
+            .filter(|key| !left.contains_key(*key))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut right = match missing.is_empty() {
+            true => TemplateMap::default(),
+            false => self.default.parse_keys(&missing)?,
+        };
+        self.overridden
+            .retain(|namespace| !keys.contains(namespace));
+        self.overridden.extend(left.keys().cloned());
+        right.extend(left);
+        Ok(right)
+    }
 }
 
 impl<D, P> std::fmt::Debug for PartialStore<D, P>
@@ -155,116 +522,1771 @@ where
     }
 }
 
-/// A memory-backed store for a template
-pub struct MemoryStore {
-    data: String,
-    changed: bool,
-    loader: LoadFunction,
+/// A store that merges the templates from several stores
+///
+/// The stores are parsed in order, with the namespaces of later stores replacing those of earlier stores.
+///
+/// This can be used to combine stores of different formats into a single store.
+///
+/// When some of the stores changed, only their namespaces are refreshed, see `TemplateStore::changed_keys`.
+/// The other stores are only parsed if they have one of those namespaces too.
+pub struct MergedStore<S> {
+    stores: Vec<S>,
+    layers: Vec<Layer>,
 }
 
-impl std::fmt::Debug for MemoryStore {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("MemoryStore")
-            .field("data", &self.data)
-            .field("changed", &self.changed)
-            .finish()
-    }
+// what a `MergedStore` knows about one of its stores, for `changed_keys`
+#[derive(Default)]
+struct Layer {
+    namespaces: HashSet<String>,
+    changed: bool,
+    // the namespaces the store said changed
+    keys: Option<Vec<String>>,
+    // the whole map of a store that can't tell which namespaces changed
+    parsed: Option<TemplateMap<String>>,
 }
 
-impl MemoryStore {
-    /// Create a new store for the templates in `data`
-    pub fn new(data: impl Into<String>, loader: LoadFunction) -> Self {
+impl<S> MergedStore<S> {
+    /// Create a new `MergedStore` from these stores
+    pub fn new(stores: impl IntoIterator<Item = S>) -> Self
+    where
+        S: TemplateStore,
+    {
         Self {
-            data: data.into(),
-            changed: true,
-            loader,
+            stores: stores.into_iter().collect(),
+            layers: vec![],
         }
     }
 
-    /// Update the templates with `data` (replaces it)
-    pub fn update(&mut self, data: impl Into<String>) {
-        self.changed = true;
-        self.data = data.into()
+    /// Get a reference to the inner stores
+    pub fn stores(&self) -> &[S] {
+        &self.stores
     }
-}
 
-impl TemplateStore for MemoryStore {
-    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
-        self.changed = false;
-        (self.loader)(&self.data)
+    /// Get a mutable reference to the inner stores
+    pub fn stores_mut(&mut self) -> &mut Vec<S> {
+        &mut self.stores
     }
 
-    fn changed(&mut self) -> bool {
-        self.changed
+    /// Consume this wrapper, returning the inner stores
+    pub fn into_inner(self) -> Vec<S> {
+        self.stores
     }
 }
 
-/// A store that always returns an error
-#[derive(Clone, Copy, Default, Debug)]
-pub struct NullStore {}
-
-impl NullStore {
-    /// Create a new NullStore
-    pub const fn new() -> Self {
-        Self {}
+impl MergedStore<FileStore> {
+    /// Create a new `MergedStore` from files, each with its own loader
+    pub fn from_files<P>(files: impl IntoIterator<Item = (P, LoadFunction)>) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self::new(
+            files.into_iter().map(|(file, loader)| {
+                FileStore::with_loader(file.into(), Loader::Text(loader), false)
+            }),
+        )
     }
 }
 
-impl TemplateStore for NullStore {
-    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
-        Err(Error::Io(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "NullStore will always be empty",
-        )))
+impl MergedStore<MemoryStore> {
+    /// Create a new `MergedStore` from strings, each with its own loader
+    pub fn from_memory<D>(data: impl IntoIterator<Item = (D, LoadFunction)>) -> Self
+    where
+        D: Into<String>,
+    {
+        Self::new(
+            data.into_iter()
+                .map(|(data, loader)| MemoryStore::new(data, loader)),
+        )
     }
+}
 
-    fn changed(&mut self) -> bool {
-        false
+impl<S: TemplateStore> MergedStore<S> {
+    fn merge(
+        &mut self,
+        mut parse: impl FnMut(&mut S) -> Result<TemplateMap<String>, Error>,
+    ) -> Result<TemplateMap<String>, Error> {
+        let mut map = TemplateMap::default();
+        let mut layers = Vec::with_capacity(self.stores.len());
+        for store in &mut self.stores {
+            let parsed = parse(store)?;
+            layers.push(Layer {
+                namespaces: parsed.keys().cloned().collect(),
+                ..Layer::default()
+            });
+            map.extend(parsed);
+        }
+        self.layers = layers;
+        log::trace!("after merge: total: {}", map.len());
+        Ok(map)
     }
 }
 
-impl<T> TemplateStore for Option<T>
-where
-    T: TemplateStore,
-{
+impl<S: TemplateStore> TemplateStore for MergedStore<S> {
     fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
-        self.as_mut()
-            .ok_or_else(|| {
-                std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    "None store always returns an error",
-                )
-            })?
-            .parse_map()
+        self.merge(S::parse_map)
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.merge(S::initial_load)
     }
 
     fn changed(&mut self) -> bool {
-        // TODO make this do something
-        // self.as_mut().map(|s| s.changed()).unwrap_or(true)
-        true
+        // every store has to be checked so they can all update their state
+        let mut changed = false;
+        for (i, store) in self.stores.iter_mut().enumerate() {
+            let store_changed = store.changed();
+            if let Some(layer) = self.layers.get_mut(i) {
+                layer.changed = store_changed;
+            }
+            changed |= store_changed;
+        }
+        changed
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        // the stores were changed (with `stores_mut`) since they were parsed
+        if self.layers.len() != self.stores.len() {
+            return None;
+        }
+
+        let mut keys = vec![];
+        for (store, layer) in self.stores.iter_mut().zip(&mut self.layers) {
+            if !layer.changed {
+                continue;
+            }
+            match store.changed_keys() {
+                Some(changed) => {
+                    keys.extend(changed.iter().cloned());
+                    layer.keys.replace(changed);
+                }
+                None => {
+                    // this store is parsed now, to find the namespaces it added or removed.
+                    // an error is left for the full parse to report
+                    let parsed = store.parse_map().ok()?;
+                    keys.extend(layer.namespaces.iter().cloned());
+                    keys.extend(parsed.keys().cloned());
+                    layer.parsed.replace(parsed);
+                }
+            }
+        }
+        keys.sort();
+        keys.dedup();
+        Some(keys)
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        if self.layers.len() != self.stores.len() {
+            let mut map = self.parse_map()?;
+            map.retain(|key, _| keys.contains(key));
+            return Ok(map);
+        }
+
+        let mut map = TemplateMap::default();
+        for (store, layer) in self.stores.iter_mut().zip(&mut self.layers) {
+            let changed = layer.keys.take().unwrap_or_default();
+            let part = match layer.parsed.take() {
+                Some(mut parsed) => {
+                    layer.namespaces = parsed.keys().cloned().collect();
+                    parsed.retain(|key, _| keys.contains(key));
+                    parsed
+                }
+                None => {
+                    // only the stores that have (or had) one of the namespaces are parsed
+                    let keys = keys
+                        .iter()
+                        .filter(|key| layer.namespaces.contains(*key) || changed.contains(key))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if keys.is_empty() {
+                        continue;
+                    }
+                    let part = store.parse_keys(&keys)?;
+                    layer
+                        .namespaces
+                        .retain(|namespace| !keys.contains(namespace));
+                    layer.namespaces.extend(part.keys().cloned());
+                    part
+                }
+            };
+            map.extend(part);
+        }
+        Ok(map)
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        self.stores
+            .iter()
+            .try_fold(false, |changed, store| Ok(store.probe()? || changed))
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        self.stores
+            .iter()
+            .filter_map(|store| store.last_modified(namespace, variant))
+            .max()
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        let names = self
+            .stores
+            .iter()
+            .map(|store| store.name())
+            .collect::<Vec<_>>();
+        format!("[{}]", names.join(", ")).into()
     }
 }
 
-impl<T> TemplateStore for Box<T>
+impl<S> std::fmt::Debug for MergedStore<S>
 where
-    T: TemplateStore + ?Sized,
+    S: std::fmt::Debug,
 {
-    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
-        <T as TemplateStore>::parse_map(&mut *self)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MergedStore")
+            .field("stores", &self.stores)
+            .finish()
     }
-    fn changed(&mut self) -> bool {
-        <T as TemplateStore>::changed(&mut *self)
+}
+
+/// A store that transforms the keys of another store
+///
+/// The function is applied to both the namespace and the variant keys when the map is parsed.
+///
+/// Keys that are the same after the transform are merged: the variants of such namespaces are combined,
+/// and of such variants, the one whose original key sorts last is kept.
+pub struct MapKeysStore<S> {
+    store: S,
+    map: fn(&str) -> String,
+    // the namespaces of `store`, before they were transformed
+    namespaces: HashSet<String>,
+    changed: Vec<String>,
+}
+
+impl<S> MapKeysStore<S> {
+    /// Create a new `MapKeysStore` which applies `map` to each key produced by `store`
+    pub fn new(store: S, map: fn(&str) -> String) -> Self
+    where
+        S: TemplateStore,
+    {
+        Self {
+            store,
+            map,
+            namespaces: HashSet::new(),
+            changed: vec![],
+        }
+    }
+
+    /// Create a new `MapKeysStore` which normalizes each key produced by `store` with `normalizer`
+    ///
+    /// The keys are normalized once, when the map is parsed, so the lookups don't have to be.
+    pub fn normalized(store: S, normalizer: KeyNormalizer) -> Self
+    where
+        S: TemplateStore,
+    {
+        let map = match normalizer {
+            KeyNormalizer::Lowercase => str::to_lowercase,
+            KeyNormalizer::SnakeCase => to_snake_case,
+        };
+        Self::new(store, map)
+    }
+
+    /// Get a reference to the inner store
+    pub const fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Get a mutable reference to the inner store
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Consume this wrapper, returning the inner store
+    pub fn into_inner(self) -> S {
+        self.store
     }
 }
 
-impl<'a, T> TemplateStore for &'a mut T
-where
-    T: TemplateStore,
-{
+impl<S: TemplateStore> MapKeysStore<S> {
+    fn map_keys(&self, templates: TemplateMap<String>) -> TemplateMap<String> {
+        let map = self.map;
+        // sorted, so colliding keys are merged in a stable order
+        let mut namespaces = templates.into_iter().collect::<Vec<_>>();
+        namespaces.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        let mut templates = TemplateMap::default();
+        for (namespace, mapping) in namespaces {
+            let mut variants = mapping.into_iter().collect::<Vec<_>>();
+            variants.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+            let mapping = templates
+                .entry(map(&namespace))
+                .or_insert_with(Mapping::default);
+            for (variant, template) in variants {
+                mapping.insert(map(&variant), template);
+            }
+        }
+        templates
+    }
+}
+
+impl<S: TemplateStore> TemplateStore for MapKeysStore<S> {
     fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
-        <T as TemplateStore>::parse_map(&mut *self)
+        let templates = self.store.parse_map()?;
+        self.namespaces = templates.keys().cloned().collect();
+        Ok(self.map_keys(templates))
     }
-    fn changed(&mut self) -> bool {
-        <T as TemplateStore>::changed(&mut *self)
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        let templates = self.store.initial_load()?;
+        self.namespaces = templates.keys().cloned().collect();
+        Ok(self.map_keys(templates))
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        let changed = self.store.changed_keys()?;
+        let mut keys = changed
+            .iter()
+            .map(|key| (self.map)(key))
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        self.changed = changed;
+        Some(keys)
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        let map = self.map;
+        // every namespace that is transformed into one of the keys, so merged namespaces stay complete
+        let changed = std::mem::take(&mut self.changed);
+        let mut namespaces = self
+            .namespaces
+            .iter()
+            .chain(&changed)
+            .filter(|namespace| keys.contains(&map(namespace)))
+            .cloned()
+            .collect::<Vec<_>>();
+        namespaces.sort();
+        namespaces.dedup();
+
+        let templates = self.store.parse_keys(&namespaces)?;
+        self.namespaces
+            .retain(|namespace| !namespaces.contains(namespace));
+        self.namespaces.extend(templates.keys().cloned());
+        Ok(self.map_keys(templates))
+    }
+
+    fn changed(&mut self) -> bool {
+        self.store.changed()
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        self.store.probe()
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        self.store.last_modified(namespace, variant)
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.store.name()
+    }
+}
+
+impl<S> std::fmt::Debug for MapKeysStore<S>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MapKeysStore")
+            .field("store", &self.store)
+            .finish()
+    }
+}
+
+/// How `MapKeysStore::normalized` normalizes the keys
+#[derive(Debug, Copy, Clone, PartialEq, Hash, Eq)]
+#[non_exhaustive]
+pub enum KeyNormalizer {
+    /// Lowercase the keys, e.g. `HelloWorld` to `helloworld`
+    Lowercase,
+    /// Snake case the keys, e.g. `HelloWorld` (or `hello-world`) to `hello_world`
+    ///
+    /// This matches the default casing of the derive
+    SnakeCase,
+}
+
+// a new word starts at an uppercase letter after a lowercase letter or a digit, or at the
+// last uppercase letter of a run followed by a lowercase letter (`HTTPResponse` is `http_response`)
+fn to_snake_case(key: &str) -> String {
+    let mut output = String::with_capacity(key.len() + 4);
+    let mut chars = key.chars().peekable();
+    let mut prev: Option<char> = None;
+    while let Some(ch) = chars.next() {
+        if matches!(ch, '-' | '_' | ' ') {
+            if !output.is_empty() && !output.ends_with('_') {
+                output.push('_');
+            }
+            prev = None;
+            continue;
+        }
+
+        if ch.is_uppercase() {
+            let boundary = match prev {
+                Some(prev) if prev.is_lowercase() || prev.is_numeric() => true,
+                Some(prev) if prev.is_uppercase() => chars.peek().is_some_and(|c| c.is_lowercase()),
+                _ => false,
+            };
+            if boundary && !output.ends_with('_') {
+                output.push('_');
+            }
+        }
+
+        output.extend(ch.to_lowercase());
+        prev = Some(ch);
+    }
+
+    if output.ends_with('_') {
+        output.pop();
+    }
+    output
+}
+
+/// A store that limits how many entries another store can produce
+///
+/// The entries are counted as the total number of variants across all namespaces.
+pub struct LimitedStore<S> {
+    store: S,
+    max_entries: usize,
+    // the entries of each namespace, so `parse_keys` can count the total
+    entries: HashMap<String, usize>,
+}
+
+impl<S> LimitedStore<S> {
+    /// Create a new `LimitedStore` which errors when `store` produces more than `max_entries`
+    pub fn new(store: S, max_entries: usize) -> Self
+    where
+        S: TemplateStore,
+    {
+        Self {
+            store,
+            max_entries,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Get the maximum number of entries allowed
+    pub const fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    /// Get a reference to the inner store
+    pub const fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Get a mutable reference to the inner store
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Consume this wrapper, returning the inner store
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+impl<S: TemplateStore> LimitedStore<S> {
+    // `kept` is the number of entries of the namespaces that weren't parsed
+    fn check(&mut self, map: &TemplateMap<String>, kept: usize) -> Result<(), Error> {
+        let entries = kept + map.values().map(|mapping| mapping.len()).sum::<usize>();
+        if entries > self.max_entries {
+            return Err(Error::TooManyEntries {
+                entries,
+                max: self.max_entries,
+            });
+        }
+        let counts = map
+            .iter()
+            .map(|(namespace, mapping)| (namespace.clone(), mapping.len()));
+        self.entries.extend(counts);
+        Ok(())
+    }
+}
+
+impl<S: TemplateStore> TemplateStore for LimitedStore<S> {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        let map = self.store.parse_map()?;
+        self.entries.clear();
+        self.check(&map, 0)?;
+        Ok(map)
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        let map = self.store.initial_load()?;
+        self.entries.clear();
+        self.check(&map, 0)?;
+        Ok(map)
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        self.store.changed_keys()
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        let map = self.store.parse_keys(keys)?;
+        self.entries
+            .retain(|namespace, _| !keys.contains(namespace));
+        let kept = self.entries.values().sum();
+        self.check(&map, kept)?;
+        Ok(map)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.store.changed()
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        self.store.probe()
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        self.store.last_modified(namespace, variant)
+    }
+
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        self.store.source(namespace, variant)
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.store.name()
+    }
+}
+
+impl<S> std::fmt::Debug for LimitedStore<S>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LimitedStore")
+            .field("store", &self.store)
+            .field("max_entries", &self.max_entries)
+            .finish()
+    }
+}
+
+/// A store that trims the leading and trailing whitespace from another store's templates
+///
+/// This removes e.g. the trailing newline of YAML block scalars, or of multi-line TOML strings,
+/// when the map is parsed. Intentional whitespace (like a quoted template in `load_lines`) is trimmed too.
+pub struct TrimStore<S> {
+    store: S,
+}
+
+impl<S> TrimStore<S> {
+    /// Create a new `TrimStore` which trims the templates produced by `store`
+    pub fn new(store: S) -> Self
+    where
+        S: TemplateStore,
+    {
+        Self { store }
+    }
+
+    /// Get a reference to the inner store
+    pub const fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Get a mutable reference to the inner store
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Consume this wrapper, returning the inner store
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+fn trim_templates(map: TemplateMap<String>) -> TemplateMap<String> {
+    map.into_iter()
+        .map(|(namespace, mapping)| {
+            let mapping = mapping
+                .into_iter()
+                .map(|(variant, template)| match template.trim() {
+                    trimmed if trimmed.len() == template.len() => (variant, template),
+                    trimmed => (variant, trimmed.to_string()),
+                })
+                .collect();
+            (namespace, mapping)
+        })
+        .collect()
+}
+
+impl<S: TemplateStore> TemplateStore for TrimStore<S> {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.store.parse_map().map(trim_templates)
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.store.initial_load().map(trim_templates)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.store.changed()
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        self.store.probe()
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        self.store.last_modified(namespace, variant)
+    }
+
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        self.store.source(namespace, variant)
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.store.name()
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        self.store.changed_keys()
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        self.store.parse_keys(keys).map(trim_templates)
+    }
+}
+
+impl<S> std::fmt::Debug for TrimStore<S>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrimStore")
+            .field("store", &self.store)
+            .finish()
+    }
+}
+
+/// A store that retries parsing another store when it fails
+///
+/// After each failed attempt this sleeps, doubling the delay each time (until it would overflow). If every
+/// attempt fails the last error is returned, so `Templates` keeps its current map.
+pub struct RetryStore<S> {
+    store: S,
+    retries: usize,
+    backoff: Duration,
+}
+
+impl<S> RetryStore<S> {
+    /// Create a new `RetryStore` which retries `store` up to `retries` times, starting with a delay of `backoff`
+    pub fn new(store: S, retries: usize, backoff: Duration) -> Self
+    where
+        S: TemplateStore,
+    {
+        Self {
+            store,
+            retries,
+            backoff,
+        }
+    }
+
+    /// Get a reference to the inner store
+    pub const fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Get a mutable reference to the inner store
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Consume this wrapper, returning the inner store
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+impl<S: TemplateStore> RetryStore<S> {
+    fn retry<T>(&mut self, mut f: impl FnMut(&mut S) -> Result<T, Error>) -> Result<T, Error> {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match f(&mut self.store) {
+                Err(err) if attempt < self.retries => {
+                    attempt += 1;
+                    log::debug!("RetryStore attempt {} failed: {}", attempt, err);
+                    std::thread::sleep(backoff);
+                    backoff = backoff.checked_mul(2).unwrap_or(backoff);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<S: TemplateStore> TemplateStore for RetryStore<S> {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.retry(S::parse_map)
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.retry(S::initial_load)
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        self.store.changed_keys()
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        self.retry(|store| store.parse_keys(keys))
+    }
+
+    fn changed(&mut self) -> bool {
+        self.store.changed()
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        self.store.probe()
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        self.store.last_modified(namespace, variant)
+    }
+
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        self.store.source(namespace, variant)
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.store.name()
+    }
+}
+
+impl<S> std::fmt::Debug for RetryStore<S>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryStore")
+            .field("store", &self.store)
+            .field("retries", &self.retries)
+            .field("backoff", &self.backoff)
+            .finish()
+    }
+}
+
+/// A store that measures how long another store takes
+///
+/// After each `initial_load`, `parse_map`, `parse_keys` and `changed` call, `report` is called
+/// with the store's name, the name of the call and how long it took.
+///
+/// # Example
+/// ```rust
+/// # use template::{load_lines, MemoryStore, Templates, TimedStore};
+/// let store = MemoryStore::new("response.hello = hello!", load_lines);
+/// let store = TimedStore::new(store, |name, call, elapsed| {
+///     println!("{}: {} took {:?}", name, call, elapsed)
+/// });
+/// let templates = Templates::new(store).unwrap();
+/// ```
+pub struct TimedStore<S, F> {
+    store: S,
+    report: F,
+}
+
+impl<S, F> TimedStore<S, F> {
+    /// Create a new `TimedStore` which calls `report` with the duration of each call to `store`
+    pub fn new(store: S, report: F) -> Self
+    where
+        S: TemplateStore,
+        F: FnMut(&str, &'static str, Duration),
+    {
+        Self { store, report }
+    }
+
+    /// Get a reference to the inner store
+    pub const fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Get a mutable reference to the inner store
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Consume this wrapper, returning the inner store
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+impl<S, F> TimedStore<S, F>
+where
+    S: TemplateStore,
+    F: FnMut(&str, &'static str, Duration),
+{
+    fn timed<T>(&mut self, call: &'static str, f: impl FnOnce(&mut S) -> T) -> T {
+        let start = std::time::Instant::now();
+        let out = f(&mut self.store);
+        (self.report)(&self.store.name(), call, start.elapsed());
+        out
+    }
+}
+
+impl<S, F> TemplateStore for TimedStore<S, F>
+where
+    S: TemplateStore,
+    F: FnMut(&str, &'static str, Duration),
+{
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.timed("parse_map", S::parse_map)
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.timed("initial_load", S::initial_load)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.timed("changed", S::changed)
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        self.store.probe()
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        self.store.last_modified(namespace, variant)
+    }
+
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        self.store.source(namespace, variant)
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.store.name()
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        self.store.changed_keys()
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        self.timed("parse_keys", |store| store.parse_keys(keys))
+    }
+}
+
+impl<S, F> std::fmt::Debug for TimedStore<S, F>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimedStore")
+            .field("store", &self.store)
+            .finish()
+    }
+}
+
+/// A store that expands `${env:NAME}` references in another store's templates
+///
+/// The references are replaced with the value of the environment variable when the map is parsed,
+/// not each time a template is applied.
+///
+/// # Example
+/// ```rust
+/// # use template::{load_lines, EnvStore, MemoryStore, Templates};
+/// std::env::set_var("SUPPORT_URL", "https://example.com/help");
+/// let store = MemoryStore::new("response.help = see ${env:SUPPORT_URL}", load_lines);
+/// let templates = Templates::new(EnvStore::new(store)).unwrap();
+/// let help = templates.get("response").and_then(|m| m.get("help"));
+/// assert_eq!(help.unwrap(), "see https://example.com/help");
+/// ```
+#[cfg(feature = "env-interp")]
+pub struct EnvStore<S> {
+    store: S,
+    strict: bool,
+}
+
+#[cfg(feature = "env-interp")]
+impl<S> EnvStore<S> {
+    /// Create a new `EnvStore` which leaves references to missing environment variables as they are
+    pub fn new(store: S) -> Self
+    where
+        S: TemplateStore,
+    {
+        Self {
+            store,
+            strict: false,
+        }
+    }
+
+    /// Create a new `EnvStore` which fails to parse when a referenced environment variable is missing
+    pub fn new_strict(store: S) -> Self
+    where
+        S: TemplateStore,
+    {
+        Self {
+            store,
+            strict: true,
+        }
+    }
+
+    /// Get a reference to the inner store
+    pub const fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Get a mutable reference to the inner store
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Consume this wrapper, returning the inner store
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    fn expand_map(&self, map: TemplateMap<String>) -> Result<TemplateMap<String>, Error> {
+        map.into_iter()
+            .map(|(namespace, mapping)| {
+                let mapping = mapping
+                    .into_iter()
+                    .map(|(variant, template)| Ok((variant, self.expand(&template)?)))
+                    .collect::<Result<_, Error>>()?;
+                Ok((namespace, mapping))
+            })
+            .collect()
+    }
+
+    fn expand(&self, input: &str) -> Result<String, Error> {
+        const PREFIX: &str = "${env:";
+
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some(head) = rest.find(PREFIX) {
+            let tail = match rest[head..].find('}') {
+                Some(tail) => head + tail,
+                None => break,
+            };
+
+            output.push_str(&rest[..head]);
+
+            let name = rest[head + PREFIX.len()..tail].trim();
+            match std::env::var(name) {
+                Ok(value) => output.push_str(&value),
+                Err(..) if self.strict => return Err(Error::MissingEnv(name.to_string())),
+                Err(..) => output.push_str(&rest[head..=tail]),
+            }
+
+            rest = &rest[tail + 1..];
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+#[cfg(feature = "env-interp")]
+impl<S: TemplateStore> TemplateStore for EnvStore<S> {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        let map = self.store.parse_map()?;
+        self.expand_map(map)
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        let map = self.store.initial_load()?;
+        self.expand_map(map)
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        self.store.changed_keys()
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        let map = self.store.parse_keys(keys)?;
+        self.expand_map(map)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.store.changed()
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        self.store.probe()
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        self.store.last_modified(namespace, variant)
+    }
+
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        self.store.source(namespace, variant)
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.store.name()
+    }
+}
+
+#[cfg(feature = "env-interp")]
+impl<S> std::fmt::Debug for EnvStore<S>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnvStore")
+            .field("store", &self.store)
+            .field("strict", &self.strict)
+            .finish()
+    }
+}
+
+/// A store that extracts the templates from a `figment::Figment`
+///
+/// The templates can be the entire figment, or a section of it (see `with_section`).
+/// A figment doesn't know when its sources change, so `changed` is driven by a revision
+/// supplied with `set_revision`. A different revision than the last loaded one is a change.
+///
+/// # Example
+/// ```rust
+/// # use template::{FigmentStore, Templates};
+/// use figment::{providers::Serialized, Figment};
+/// let figment = Figment::from(Serialized::default("templates.response.hello", "hello ${name}!"));
+/// let store = FigmentStore::new(figment).with_section("templates");
+/// let templates = Templates::new(store).unwrap();
+/// assert!(templates.get("response").is_some());
+/// ```
+#[cfg(feature = "figment")]
+pub struct FigmentStore {
+    figment: figment::Figment,
+    section: Option<String>,
+    revision: u64,
+    loaded: Option<u64>,
+}
+
+#[cfg(feature = "figment")]
+impl FigmentStore {
+    /// Create a new `FigmentStore` which extracts the templates from the entire `figment`
+    pub fn new(figment: figment::Figment) -> Self {
+        Self {
+            figment,
+            section: None,
+            revision: 0,
+            loaded: None,
+        }
+    }
+
+    /// Extract the templates from the section at this (dotted) key, rather than the entire figment
+    pub fn with_section(mut self, section: impl Into<String>) -> Self {
+        self.section.replace(section.into());
+        self
+    }
+
+    /// Set the revision of the figment's sources
+    ///
+    /// The store has changed when this is different from the revision that was last loaded
+    pub fn set_revision(&mut self, revision: u64) {
+        self.revision = revision;
+    }
+
+    /// Get the current revision
+    pub const fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Get a reference to the inner figment
+    pub const fn inner(&self) -> &figment::Figment {
+        &self.figment
+    }
+
+    /// Get a mutable reference to the inner figment
+    ///
+    /// Changes to it are only loaded after the revision is changed
+    pub fn inner_mut(&mut self) -> &mut figment::Figment {
+        &mut self.figment
+    }
+
+    /// Consume this store, returning the inner figment
+    pub fn into_inner(self) -> figment::Figment {
+        self.figment
+    }
+}
+
+#[cfg(feature = "figment")]
+impl TemplateStore for FigmentStore {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        let map = match &self.section {
+            Some(section) => self.figment.extract_inner(section),
+            None => self.figment.extract(),
+        };
+        self.loaded.replace(self.revision);
+        map.map_err(Error::from)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.loaded != Some(self.revision)
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        Ok(self.loaded != Some(self.revision))
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        match &self.section {
+            Some(section) => format!("figment ({})", section).into(),
+            None => Cow::Borrowed("figment"),
+        }
+    }
+}
+
+#[cfg(feature = "figment")]
+impl std::fmt::Debug for FigmentStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FigmentStore")
+            .field("figment", &self.figment)
+            .field("section", &self.section)
+            .field("revision", &self.revision)
+            .field("loaded", &self.loaded)
+            .finish()
+    }
+}
+
+/// A store for templates embedded in the binary with [`include_dir!`](include_dir::include_dir)
+///
+/// Every file in the directory (and its subdirectories) is parsed with the loader, in path order,
+/// with the namespaces of later files replacing those of earlier files.
+///
+/// The embedded files can't change, so this store has only changed until it has been loaded.
+///
+/// # Example
+/// ```rust,ignore
+/// use include_dir::{include_dir, Dir};
+/// static TEMPLATES: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates");
+///
+/// let store = template::IncludeDirStore::new(&TEMPLATES, template::load_toml);
+/// let templates = template::Templates::new(store).unwrap();
+/// ```
+#[cfg(feature = "include_dir")]
+#[derive(Clone, Copy)]
+pub struct IncludeDirStore {
+    dir: &'static include_dir::Dir<'static>,
+    loader: LoadFunction,
+    loaded: bool,
+}
+
+#[cfg(feature = "include_dir")]
+impl IncludeDirStore {
+    /// Create a new store for the files embedded in `dir`, parsing each with `loader`
+    pub const fn new(dir: &'static include_dir::Dir<'static>, loader: LoadFunction) -> Self {
+        Self {
+            dir,
+            loader,
+            loaded: false,
+        }
+    }
+
+    /// Get the embedded directory
+    pub const fn inner(&self) -> &'static include_dir::Dir<'static> {
+        self.dir
+    }
+
+    fn files(
+        dir: &'static include_dir::Dir<'static>,
+        files: &mut Vec<&'static include_dir::File<'static>>,
+    ) {
+        for entry in dir.entries() {
+            match entry {
+                include_dir::DirEntry::Dir(dir) => Self::files(dir, files),
+                include_dir::DirEntry::File(file) => files.push(file),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "include_dir")]
+impl TemplateStore for IncludeDirStore {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        let mut files = vec![];
+        Self::files(self.dir, &mut files);
+        files.sort_by_key(|file| file.path());
+
+        let mut map = TemplateMap::default();
+        for file in files {
+            let data = file.contents_utf8().ok_or_else(|| {
+                let err = std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "embedded file is not valid UTF-8",
+                );
+                Error::io_at(file.path(), err)
+            })?;
+            log::trace!("loading embedded file: {}", file.path().display());
+            map.extend((self.loader)(data)?);
+        }
+
+        self.loaded = true;
+        Ok(map)
+    }
+
+    fn changed(&mut self) -> bool {
+        !self.loaded
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        Ok(!self.loaded)
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        match self.dir.path() {
+            path if path.as_os_str().is_empty() => Cow::Borrowed("embedded"),
+            path => format!("embedded ({})", path.display()).into(),
+        }
+    }
+}
+
+#[cfg(feature = "include_dir")]
+impl std::fmt::Debug for IncludeDirStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IncludeDirStore")
+            .field("dir", &self.dir.path())
+            .field("loaded", &self.loaded)
+            .finish()
+    }
+}
+
+/// A store for the template files in a zip archive
+///
+/// Every file in the archive is parsed with the loader for its extension, in name order, with the
+/// namespaces of later files replacing those of earlier files. Files without a loader are skipped.
+///
+/// The loaders for the enabled formats are registered by default: `.json`, `.toml`, and `.yaml` / `.yml`.
+///
+/// Changes are detected by the archive's modification time.
+#[cfg(feature = "zip")]
+pub struct ArchiveStore {
+    file: PathBuf,
+    loaders: Vec<(String, LoadFunction)>,
+    last: Option<SystemTime>,
+}
+
+#[cfg(feature = "zip")]
+impl ArchiveStore {
+    /// Create a store for the archive at this `PathBuf`
+    ///
+    /// The archive isn't read until the templates are parsed
+    pub fn new(file: PathBuf) -> Self {
+        let loaders: Vec<(String, LoadFunction)> = vec![
+            #[cfg(feature = "json")]
+            ("json".into(), crate::load_json),
+            #[cfg(feature = "toml")]
+            ("toml".into(), crate::load_toml),
+            #[cfg(feature = "yaml")]
+            ("yaml".into(), crate::load_yaml),
+            #[cfg(feature = "yaml")]
+            ("yml".into(), crate::load_yaml),
+        ];
+
+        Self {
+            file,
+            loaders,
+            last: None,
+        }
+    }
+
+    /// Use `loader` for the files with this extension, replacing any previous loader for it
+    ///
+    /// The extension is matched case-insensitively, without the leading `.`
+    pub fn with_loader(mut self, extension: impl Into<String>, loader: LoadFunction) -> Self {
+        let extension = extension.into().to_ascii_lowercase();
+        self.loaders.retain(|(ext, _)| *ext != extension);
+        self.loaders.push((extension, loader));
+        self
+    }
+
+    fn loader(&self, name: &str) -> Option<LoadFunction> {
+        let extension = std::path::Path::new(name).extension()?.to_str()?;
+        self.loaders
+            .iter()
+            .find(|(ext, _)| ext.eq_ignore_ascii_case(extension))
+            .map(|&(_, loader)| loader)
+    }
+
+    fn zip_error(&self, err: zip::result::ZipError) -> Error {
+        match err {
+            zip::result::ZipError::Io(err) => Error::io_at(&self.file, err),
+            err => Error::Deserialize(Box::new(err)),
+        }
+    }
+}
+
+#[cfg(feature = "zip")]
+impl TemplateStore for ArchiveStore {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        use std::io::Read as _;
+
+        // this is taken before the read, so an edit during it is seen by the next `changed`
+        let modified = std::fs::metadata(&self.file).and_then(|md| md.modified());
+        let file = std::fs::File::open(&self.file).map_err(|err| Error::io_at(&self.file, err))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|err| self.zip_error(err))?;
+
+        let mut names = archive
+            .file_names()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        names.sort();
+
+        let mut map = TemplateMap::default();
+        for name in names {
+            let loader = match self.loader(&name) {
+                Some(loader) => loader,
+                None => {
+                    log::debug!("ArchiveStore has no loader for: {}", name);
+                    continue;
+                }
+            };
+
+            let mut entry = archive.by_name(&name).map_err(|err| self.zip_error(err))?;
+            if entry.is_dir() {
+                continue;
+            }
+            let mut data = String::new();
+            entry
+                .read_to_string(&mut data)
+                .map_err(|err| Error::io_at(self.file.join(&name), err))?;
+
+            log::trace!("loading archive file: {}", name);
+            map.extend(loader(&data)?);
+        }
+
+        self.last = modified.ok();
+        Ok(map)
+    }
+
+    fn changed(&mut self) -> bool {
+        let time = match std::fs::metadata(&self.file).and_then(|md| md.modified()) {
+            Ok(time) => time,
+            Err(..) => return false,
+        };
+        if self.last.is_some_and(|prev| time <= prev) {
+            return false;
+        }
+        log::debug!("ArchiveStore changed");
+        self.last.replace(time);
+        true
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        match std::fs::metadata(&self.file).and_then(|md| md.modified()) {
+            Ok(time) => Ok(self.last.is_none_or(|prev| time > prev)),
+            Err(err) => Err(Error::io_at(&self.file, err)),
+        }
+    }
+
+    fn last_modified(&self, _namespace: &str, _variant: &str) -> Option<SystemTime> {
+        std::fs::metadata(&self.file)
+            .and_then(|md| md.modified())
+            .ok()
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        self.file.to_string_lossy()
+    }
+}
+
+#[cfg(feature = "zip")]
+impl std::fmt::Debug for ArchiveStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let extensions = self.loaders.iter().map(|(ext, _)| ext).collect::<Vec<_>>();
+        f.debug_struct("ArchiveStore")
+            .field("file", &self.file)
+            .field("extensions", &extensions)
+            .field("last", &self.last)
+            .finish()
+    }
+}
+
+/// A memory-backed store for a template
+#[derive(Clone)]
+pub struct MemoryStore {
+    data: String,
+    changed: bool,
+    loader: LoadFunction,
+}
+
+impl std::fmt::Debug for MemoryStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryStore")
+            .field("data", &self.data)
+            .field("changed", &self.changed)
+            .finish()
+    }
+}
+
+impl MemoryStore {
+    /// Create a new store for the templates in `data`
+    pub fn new(data: impl Into<String>, loader: LoadFunction) -> Self {
+        Self {
+            data: data.into(),
+            changed: false,
+            loader,
+        }
+    }
+
+    /// Update the templates with `data` (replaces it)
+    pub fn update(&mut self, data: impl Into<String>) {
+        self.changed = true;
+        self.data = data.into()
+    }
+
+    /// Get the template source held by this store
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// Consume the store, returning the template source it held
+    pub fn into_data(self) -> String {
+        self.data
+    }
+}
+
+impl TemplateStore for MemoryStore {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.changed = false;
+        (self.loader)(&self.data)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.changed
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        Ok(self.changed)
+    }
+}
+
+/// A store for templates in a `&'static str`, such as embedded defaults
+///
+/// Unlike a `MemoryStore`, the data isn't copied. The data can't change, so this store has only changed
+/// until it has been loaded.
+///
+/// # Example
+/// ```rust
+/// # use template::{load_lines, StaticStore, Templates};
+/// static DEFAULTS: &str = "response.hello = hello ${name}!";
+/// let templates = Templates::new(StaticStore::new(DEFAULTS, load_lines)).unwrap();
+/// assert!(templates.get("response").is_some());
+/// ```
+#[derive(Copy, Clone)]
+pub struct StaticStore {
+    data: &'static str,
+    loader: LoadFunction,
+    loaded: bool,
+}
+
+impl std::fmt::Debug for StaticStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StaticStore")
+            .field("data", &self.data)
+            .field("loaded", &self.loaded)
+            .finish()
+    }
+}
+
+impl StaticStore {
+    /// Create a new store for the templates in `data`
+    pub const fn new(data: &'static str, loader: LoadFunction) -> Self {
+        Self {
+            data,
+            loader,
+            loaded: false,
+        }
+    }
+
+    /// Get the template source held by this store
+    pub const fn data(&self) -> &'static str {
+        self.data
+    }
+}
+
+impl TemplateStore for StaticStore {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        let map = (self.loader)(self.data)?;
+        self.loaded = true;
+        Ok(map)
+    }
+
+    fn changed(&mut self) -> bool {
+        !self.loaded
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        Ok(!self.loaded)
+    }
+}
+
+/// A store for templates read from stdin
+///
+/// Stdin is read to the end the first time the templates are parsed, and kept, as it can't be read again.
+/// So this store has only changed until it has been loaded.
+pub struct StdinStore {
+    data: Option<String>,
+    loader: LoadFunction,
+}
+
+impl std::fmt::Debug for StdinStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StdinStore")
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+impl StdinStore {
+    /// Create a new store for the templates read from stdin, parsed with `loader`
+    pub const fn new(loader: LoadFunction) -> Self {
+        Self { data: None, loader }
+    }
+
+    /// Get the template source read from stdin, if it has been read
+    pub fn data(&self) -> Option<&str> {
+        self.data.as_deref()
+    }
+}
+
+impl TemplateStore for StdinStore {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        let data = match &mut self.data {
+            Some(data) => data,
+            data @ None => {
+                use std::io::Read as _;
+                let mut input = String::new();
+                std::io::stdin().lock().read_to_string(&mut input)?;
+                data.insert(input)
+            }
+        };
+        (self.loader)(data)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.data.is_none()
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        Ok(self.data.is_none())
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed("stdin")
+    }
+}
+
+/// A store backed by a function that produces the templates
+pub struct FnStore<F> {
+    func: F,
+    changed: bool,
+    always: bool,
+}
+
+impl<F> std::fmt::Debug for FnStore<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnStore")
+            .field("changed", &self.changed)
+            .field("always", &self.always)
+            .finish()
+    }
+}
+
+impl<F> FnStore<F>
+where
+    F: FnMut() -> Result<TemplateMap<String>, Error>,
+{
+    /// Create a new store that calls `func` on every refresh
+    pub fn new(func: F) -> Self {
+        Self {
+            func,
+            changed: true,
+            always: true,
+        }
+    }
+
+    /// Create a new store that calls `func` once, and then only after `mark_changed` is called
+    pub fn once(func: F) -> Self {
+        Self {
+            func,
+            changed: false,
+            always: false,
+        }
+    }
+
+    /// Mark this store as changed, so `func` will be called on the next refresh
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+}
+
+impl<F> TemplateStore for FnStore<F>
+where
+    F: FnMut() -> Result<TemplateMap<String>, Error>,
+{
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.changed = false;
+        (self.func)()
+    }
+
+    fn changed(&mut self) -> bool {
+        self.always || self.changed
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        Ok(self.always || self.changed)
+    }
+}
+
+/// A store that always returns an error
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NullStore {}
+
+impl NullStore {
+    /// Create a new NullStore
+    pub const fn new() -> Self {
+        Self {}
+    }
+}
+
+impl TemplateStore for NullStore {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        Err(std::io::Error::other("NullStore will always be empty").into())
+    }
+
+    fn changed(&mut self) -> bool {
+        false
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        Err(std::io::Error::other("NullStore will always be empty").into())
+    }
+}
+
+impl<T> TemplateStore for Option<T>
+where
+    T: TemplateStore,
+{
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.as_mut()
+            .ok_or_else(|| std::io::Error::other("None store always returns an error"))?
+            .parse_map()
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.as_mut()
+            .ok_or_else(|| std::io::Error::other("None store always returns an error"))?
+            .initial_load()
+    }
+
+    fn changed(&mut self) -> bool {
+        // TODO make this do something
+        // self.as_mut().map(|s| s.changed()).unwrap_or(true)
+        true
+    }
+
+    fn probe(&self) -> Result<bool, Error> {
+        self.as_ref()
+            .ok_or_else(|| std::io::Error::other("None store always returns an error"))?
+            .probe()
+    }
+
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        self.as_ref()?.last_modified(namespace, variant)
+    }
+
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        self.as_ref()
+            .map_or(Source::Store, |store| store.source(namespace, variant))
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        match self {
+            Some(store) => store.name(),
+            None => Cow::Borrowed("None"),
+        }
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        self.as_mut()?.changed_keys()
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        self.as_mut()
+            .ok_or_else(|| std::io::Error::other("None store always returns an error"))?
+            .parse_keys(keys)
+    }
+}
+
+impl<T> TemplateStore for Box<T>
+where
+    T: TemplateStore + ?Sized,
+{
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        <T as TemplateStore>::parse_map(&mut **self)
+    }
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        <T as TemplateStore>::initial_load(&mut **self)
+    }
+    fn changed(&mut self) -> bool {
+        <T as TemplateStore>::changed(&mut **self)
+    }
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        <T as TemplateStore>::changed_keys(&mut **self)
+    }
+    fn probe(&self) -> Result<bool, Error> {
+        <T as TemplateStore>::probe(&**self)
+    }
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        <T as TemplateStore>::last_modified(&**self, namespace, variant)
+    }
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        <T as TemplateStore>::source(&**self, namespace, variant)
+    }
+    fn name(&self) -> Cow<'_, str> {
+        <T as TemplateStore>::name(&**self)
+    }
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        <T as TemplateStore>::parse_keys(&mut **self, keys)
+    }
+}
+
+impl<T> TemplateStore for &mut T
+where
+    T: TemplateStore,
+{
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        <T as TemplateStore>::parse_map(*self)
+    }
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        <T as TemplateStore>::initial_load(*self)
+    }
+    fn changed(&mut self) -> bool {
+        <T as TemplateStore>::changed(*self)
+    }
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        <T as TemplateStore>::changed_keys(*self)
+    }
+    fn probe(&self) -> Result<bool, Error> {
+        <T as TemplateStore>::probe(&**self)
+    }
+    fn last_modified(&self, namespace: &str, variant: &str) -> Option<SystemTime> {
+        <T as TemplateStore>::last_modified(&**self, namespace, variant)
+    }
+    fn source(&self, namespace: &str, variant: &str) -> Source {
+        <T as TemplateStore>::source(&**self, namespace, variant)
+    }
+    fn name(&self) -> Cow<'_, str> {
+        <T as TemplateStore>::name(&**self)
+    }
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        <T as TemplateStore>::parse_keys(*self, keys)
     }
 }
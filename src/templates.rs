@@ -1,25 +1,75 @@
 use std::borrow::Borrow;
 use std::fmt::Display;
 use std::hash::Hash;
+use std::time::{Duration, Instant};
 
-use super::{Error, Mapping, TemplateMap, TemplateStore};
+use super::{Error, Mapping, NullStore, TemplateMap, TemplateStore};
 
 /// A collection of templates backed by a `TemplateStore`
+///
+/// This is `Send` and `Sync` when the store is
 #[derive(serde::Deserialize)]
 pub struct Templates<S> {
     #[serde(skip)]
     store: S,
     templates: TemplateMap<String>,
+    #[serde(skip)]
+    keep_last_good: bool,
+    #[serde(skip)]
+    version: u64,
+    #[serde(skip)]
+    refreshed: Option<Instant>,
 }
 
 impl<S> std::fmt::Debug for Templates<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Templates")
             .field("map", &self.templates)
+            .field("keep_last_good", &self.keep_last_good)
+            .field("version", &self.version)
+            .field("refreshed", &self.refreshed)
             .finish()
     }
 }
 
+/// An empty collection, backed by a `NullStore`
+///
+/// Templates can be added with `insert`, and a store can be attached later with `attach`
+impl Default for Templates<NullStore> {
+    fn default() -> Self {
+        Self {
+            store: NullStore::new(),
+            templates: TemplateMap::default(),
+            keep_last_good: false,
+            version: 0,
+            refreshed: None,
+        }
+    }
+}
+
+/// A collection of these templates, backed by a `NullStore`
+impl From<TemplateMap<String>> for Templates<NullStore> {
+    fn from(templates: TemplateMap<String>) -> Self {
+        Self {
+            templates,
+            ..Self::default()
+        }
+    }
+}
+
+/// Templates are equal if their template maps are equal, the stores aren't compared
+impl<S, T> PartialEq<Templates<T>> for Templates<S> {
+    fn eq(&self, other: &Templates<T>) -> bool {
+        self.templates == other.templates
+    }
+}
+
+impl<S> PartialEq<TemplateMap<String>> for Templates<S> {
+    fn eq(&self, other: &TemplateMap<String>) -> bool {
+        self.templates == *other
+    }
+}
+
 impl<S> Templates<S>
 where
     S: TemplateStore,
@@ -33,34 +83,308 @@ where
         let mut this = Self {
             store,
             templates: TemplateMap::default(),
+            keep_last_good: false,
+            version: 0,
+            refreshed: None,
+        };
+        this.load().map(|_| this)
+    }
+
+    /// Attach a new store to this collection, loading the templates from it
+    ///
+    /// Once the store is loaded, its templates replace the current templates
+    ///
+    /// # Errors
+    /// - An I/O Error if the data was to be loaded from a non-existant file
+    /// - A deserialization error from the template source
+    pub fn attach<T: TemplateStore>(self, store: T) -> Result<Templates<T>, Error> {
+        let mut this = Templates {
+            store,
+            templates: self.templates,
+            keep_last_good: self.keep_last_good,
+            version: self.version,
+            refreshed: self.refreshed,
         };
-        this.refresh().map(|_| this)
+        this.load().map(|_| this)
+    }
+
+    /// Sets whether a failed refresh keeps the last good templates
+    ///
+    /// When this is set, a refresh that fails to load the store logs the error and
+    /// keeps serving the previously loaded templates, rather than returning the error.
+    /// This is off by default.
+    pub fn set_keep_last_good(&mut self, keep: bool) {
+        self.keep_last_good = keep;
+    }
+
+    /// Whether a failed refresh keeps the last good templates
+    pub fn keep_last_good(&self) -> bool {
+        self.keep_last_good
+    }
+
+    /// The version of the templates
+    ///
+    /// This increases whenever a refresh (or an insert) changes any templates, so it can
+    /// be compared to tell whether the templates changed since an earlier version.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Replace the store with `store`, loading the templates from it
+    ///
+    /// On failure the previous store and its templates are kept. The version keeps increasing.
+    ///
+    /// # Errors
+    /// - An I/O Error if the data was to be loaded from a non-existant file
+    /// - A deserialization error from the template source
+    pub fn reload_from(&mut self, store: S) -> Result<(), Error> {
+        let mut templates = Self::new(store)?;
+        templates.keep_last_good = self.keep_last_good;
+        templates.version = self.version + 1;
+        *self = templates;
+        Ok(())
+    }
+
+    /// Inserts the template for `namespace.variant`, returning the previous template
+    ///
+    /// The inserted template is only kept in memory. A refresh of this namespace
+    /// from the backing store replaces it.
+    pub fn insert(
+        &mut self,
+        namespace: impl Into<String>,
+        variant: impl Into<String>,
+        template: impl Into<String>,
+    ) -> Option<String> {
+        let (variant, template) = (variant.into(), template.into());
+        let mapping = self.templates.entry(namespace.into()).or_default();
+        if mapping.get(&variant) != Some(&template) {
+            self.version += 1;
+        }
+        mapping.insert(variant, template)
     }
 
     /// Tries to get the key (`namespace`) from the collection
     ///
     /// The returned value will let you get the value (`variant`).
-    pub fn get<K: ?Sized>(&mut self, parent: &K) -> Option<&Mapping<String>>
+    pub fn get<K>(&self, parent: &K) -> Option<&Mapping<String>>
     where
-        K: Hash + Eq + Display,
+        K: ?Sized + Hash + Eq + Display,
         String: Borrow<K>,
     {
         self.templates.get(parent)
     }
 
+    /// Expands the references to other templates in `input`
+    ///
+    /// A reference is written as `${@namespace.variant}`, and is replaced by
+    /// that template (with its own references expanded). The namespace is
+    /// everything before the last `.` in the key.
+    ///
+    /// # Errors
+    /// - A referenced template was not found
+    /// - A template references itself, directly or through other templates
+    pub fn expand_references(&self, input: &str) -> Result<String, Error> {
+        self.expand_references_inner(input, &mut vec![])
+    }
+
+    fn expand_references_inner(
+        &self,
+        input: &str,
+        seen: &mut Vec<String>,
+    ) -> Result<String, Error> {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some(head) = rest.find("${@") {
+            let tail = match rest[head..].find('}') {
+                Some(tail) => head + tail,
+                None => break,
+            };
+
+            output.push_str(&rest[..head]);
+
+            let key = rest[head + 3..tail].trim();
+            if seen.iter().any(|k| k == key) {
+                return Err(Error::RecursiveTemplate(key.to_string()));
+            }
+
+            let template = crate::parse_key(key)
+                .and_then(|(namespace, variant)| self.get(namespace)?.get(variant))
+                .ok_or_else(|| Error::NotFound(key.to_string()))?;
+
+            seen.push(key.to_string());
+            output.push_str(&self.expand_references_inner(template, seen)?);
+            seen.pop();
+
+            rest = &rest[tail + 1..];
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    /// Gets the template for `namespace.variant`, inserting the template produced by `f` if it was missing
+    ///
+    /// The inserted template is only kept in memory. A refresh of this namespace
+    /// from the backing store replaces it.
+    pub fn get_or_insert_with(
+        &mut self,
+        namespace: &str,
+        variant: &str,
+        f: impl FnOnce() -> String,
+    ) -> &String {
+        let version = &mut self.version;
+        self.templates
+            .entry(namespace.to_string())
+            .or_default()
+            .get_or_insert_with(variant.to_string(), || {
+                *version += 1;
+                f()
+            })
+    }
+
+    /// Get the underlying template map
+    ///
+    /// This does not refresh the collection
+    pub fn map(&self) -> &TemplateMap<String> {
+        &self.templates
+    }
+
+    /// An iterator over the namespaces and their mappings
+    ///
+    /// This does not refresh the collection
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, String, Mapping<String>> {
+        self.templates.iter()
+    }
+
     /// Refreshes the collection from the backing store
     ///
+    /// If the store fails to load, the current templates are left in place.
+    ///
     /// # Errors
     /// - An I/O Error if the data was to be loaded from a non-existant file
     /// - A deserialization error from the template source
+    ///
+    /// With `set_keep_last_good`, these errors are logged instead of returned.
     pub fn refresh(&mut self) -> Result<(), Error> {
-        if self.store.changed() {
-            self.templates = self.store.parse_map()?;
-            log::debug!("refreshed templates");
+        self.refresh_with(false, false).map(drop)
+    }
+
+    /// Refreshes the collection from the backing store, returning the `namespace.variant` keys that changed
+    ///
+    /// See `refresh` and `TemplateMap::diff`. Unlike `refresh`, this compares every refreshed template to the previous one.
+    ///
+    /// # Errors
+    /// - An I/O Error if the data was to be loaded from a non-existant file
+    /// - A deserialization error from the template source
+    pub fn refresh_changes(&mut self) -> Result<Vec<String>, Error> {
+        self.refresh_with(false, true)
+    }
+
+    /// Refreshes the collection from the backing store, if the last refresh was longer than `max_age` ago
+    ///
+    /// When it is stale, the templates are reparsed even if the store doesn't report a change.
+    /// This returns whether the collection was refreshed. See `refresh`
+    ///
+    /// # Errors
+    /// - An I/O Error if the data was to be loaded from a non-existant file
+    /// - A deserialization error from the template source
+    pub fn refresh_if_stale(&mut self, max_age: Duration) -> Result<bool, Error> {
+        if self.refreshed.is_some_and(|at| at.elapsed() <= max_age) {
+            return Ok(false);
         }
+        self.refresh_with(true, false).map(|_| true)
+    }
+
+    // the changed keys are only collected with `diff`, otherwise this is empty
+    fn refresh_with(&mut self, force: bool, diff: bool) -> Result<Vec<String>, Error> {
+        match self.try_refresh(force, diff) {
+            Err(err) if self.keep_last_good => {
+                log::warn!(
+                    "cannot refresh templates from {}, keeping the last good: {}",
+                    self.store.name(),
+                    err
+                );
+                Ok(vec![])
+            }
+            res => res,
+        }
+    }
+
+    // the first load from the store, see `TemplateStore::initial_load`
+    fn load(&mut self) -> Result<(), Error> {
+        self.refreshed.replace(Instant::now());
+        let map = match self.store.initial_load() {
+            Ok(map) => map,
+            Err(err) if self.keep_last_good => {
+                log::warn!(
+                    "cannot load templates from {}, keeping the last good: {}",
+                    self.store.name(),
+                    err
+                );
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+        if self.templates != map {
+            self.version += 1;
+        }
+        self.templates = map;
+        log::debug!("loaded templates");
         Ok(())
     }
 
+    fn try_refresh(&mut self, force: bool, diff: bool) -> Result<Vec<String>, Error> {
+        self.refreshed.replace(Instant::now());
+        // the store is always checked, so it can keep its change detection up to date
+        let keys = match self.store.changed() {
+            true => self.store.changed_keys(),
+            false if force => None,
+            false => return Ok(vec![]),
+        };
+
+        let (changed, changes) = match keys {
+            Some(keys) => {
+                let map = self.store.parse_keys(&keys)?;
+                let old = keys
+                    .iter()
+                    .filter_map(|key| self.templates.remove_entry(key))
+                    .collect::<TemplateMap<String>>();
+                let changed = old != map;
+                let changes = if diff { old.diff(&map) } else { vec![] };
+                self.templates.extend(map);
+                log::debug!("refreshed templates for: {}", keys.join(", "));
+                (changed, changes)
+            }
+            None => {
+                let map = self.store.parse_map()?;
+                let changed = self.templates != map;
+                let changes = if diff {
+                    self.templates.diff(&map)
+                } else {
+                    vec![]
+                };
+                self.templates = map;
+                log::debug!("refreshed templates");
+                (changed, changes)
+            }
+        };
+        if changed {
+            self.version += 1;
+        }
+        Ok(changes)
+    }
+
+    /// Checks whether the backing store is reachable, without refreshing the collection
+    ///
+    /// This returns whether the store has changed since it was last parsed, if it can tell.
+    ///
+    /// # Errors
+    /// - The store is not reachable
+    pub fn store_changed(&self) -> Result<bool, Error> {
+        self.store.probe()
+    }
+
     /// Get a reference to the inner store
     pub fn store(&self) -> &S {
         &self.store
@@ -75,4 +399,26 @@ where
     pub fn into_inner(self) -> S {
         self.store
     }
+
+    /// Consume this returning the template map
+    pub fn into_map(self) -> TemplateMap<String> {
+        self.templates
+    }
+
+    /// Consume this returning the template map and the inner store
+    pub fn into_parts(self) -> (TemplateMap<String>, S) {
+        (self.templates, self.store)
+    }
+}
+
+impl<'a, S> IntoIterator for &'a Templates<S>
+where
+    S: TemplateStore,
+{
+    type Item = (&'a String, &'a Mapping<String>);
+    type IntoIter = std::collections::hash_map::Iter<'a, String, Mapping<String>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
@@ -0,0 +1,27 @@
+use crate::{Resolver, TemplateStore};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt as _};
+
+/// How many reload events are buffered for a slow subscriber before it starts skipping them
+const CAPACITY: usize = 16;
+
+/// An event sent to the `Resolver::watch` streams when a refresh swaps the templates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReloadEvent {
+    /// The `namespace.variant` keys that were added, removed or changed, see `TemplateMap::diff`
+    pub changed: Vec<String>,
+}
+
+pub(crate) fn channel() -> broadcast::Sender<ReloadEvent> {
+    broadcast::channel(CAPACITY).0
+}
+
+impl<S: TemplateStore> Resolver<S> {
+    /// Subscribe to the reload events of this resolver
+    ///
+    /// An event is sent whenever a refresh (done by the lookups) changes any templates.
+    /// A subscriber that falls too far behind skips the oldest events.
+    pub fn watch(&self) -> impl Stream<Item = ReloadEvent> {
+        BroadcastStream::new(self.reloads.subscribe()).filter_map(Result::ok)
+    }
+}
@@ -1,125 +1,388 @@
-use heck::SnekCase as _;
+use heck::{KebabCase as _, SnekCase as _};
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, spanned::Spanned, DeriveInput, Error};
+use syn::{ext::IdentExt as _, parse_macro_input, spanned::Spanned, DeriveInput, Error};
 
-/** Derives `Template` for an enum
+// these would be treated as the conditional sections, rather than as keys
+const RESERVED: &[&str] = &["if", "else", "endif"];
+
+// mirrors `template::NameCasing`, for the casing declared by a type
+#[derive(Copy, Clone)]
+enum Casing {
+    Snake,
+    Kebab,
+    Original,
+}
+
+impl Casing {
+    fn apply(self, name: &str) -> String {
+        match self {
+            Self::Snake => name.to_snek_case(),
+            Self::Kebab => name.to_kebab_case(),
+            Self::Original => name.to_string(),
+        }
+    }
+
+    fn name_casing(self) -> proc_macro2::TokenStream {
+        match self {
+            Self::Snake => quote! { template::NameCasing::Snake },
+            Self::Kebab => quote! { template::NameCasing::Kebab },
+            Self::Original => quote! { template::NameCasing::Original },
+        }
+    }
+}
+
+// a `match casing` over each `NameCasing`, using snake case for any casing added later,
+// so older derives don't panic on it
+fn cased<T: quote::ToTokens>(f: impl Fn(Casing) -> T) -> impl quote::ToTokens {
+    let (snake, kebab, original) = (f(Casing::Snake), f(Casing::Kebab), f(Casing::Original));
+    quote! {
+        match casing {
+            template::NameCasing::Snake => { #snake }
+            template::NameCasing::Kebab => { #kebab }
+            template::NameCasing::Original => { #original }
+            _ => { #snake }
+        }
+    }
+}
+
+/** Derives `Template` for an enum, or a struct
 
     * the type must be an enum with named variants, or fieldless variants
+    * or the type must be a struct with named fields, which is treated as a single variant
     * the fields in the named variants must not be rust identifiers
     * the types in the named variants must implement `std::fmt::Display`
+    * the `bool` fields in the named variants can be used in `${if key}` conditional sections
+    * a `bool` field with `#[when_true("text")]` is bound to `text` when it is true, and to an empty string when it is false
+    * a variant with a single unnamed field of another `Template` type delegates to it (including its namespace)
+    * a struct's variant name defaults to its name, this can be changed with `#[variant("name")]` (which is used as it is, in every casing)
+    * with `#[strict]`, `apply` fails if the template has any keys the variant doesn't bind
+    * the fields cannot use the names reserved for conditional sections (`if`, `else`, `endif`)
+    * the names are looked up in snake case by default, this can be changed with `#[namespace("name", casing = "kebab")]` (or `"original"`)
+    * the separator used in `Template::key` defaults to `.`, this can be changed with `#[separator("/")]`
+    * a variant (or a struct) with `#[template("text")]` lists `text` as its default template in `Template::TEMPLATES`
+    * the `#[template("text")]` of a variant without fields cannot use any `${..}` keys
+    * an enum with `#[template_default(Variant)]` (or `#[template_default("text")]`) falls back to that variant's template (or `text`) in `Template::FALLBACK`
 */
-#[proc_macro_derive(Template, attributes(namespace))]
+#[proc_macro_derive(
+    Template,
+    attributes(
+        namespace,
+        variant,
+        strict,
+        when_true,
+        separator,
+        template,
+        template_default
+    )
+)]
 pub fn template(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
     let syn::DeriveInput {
         ident,
         generics,
         data,
-        mut attrs,
+        attrs,
         ..
     } = derive_input;
 
-    if attrs.is_empty() {
-        let mut err = Error::new_spanned(
-            quote! { attrs},
-            "A `namespace` attribute with the template name must be supplied.",
-        );
-        err.combine(Error::new_spanned(ident, "for this type"));
-        return err.to_compile_error().into();
-    }
+    let attr = match attrs.iter().find(|attr| attr.path.is_ident("namespace")) {
+        Some(attr) => attr,
+        None => {
+            let mut err = Error::new_spanned(
+                quote! { attrs},
+                "A `namespace` attribute with the template name must be supplied.",
+            );
+            err.combine(Error::new_spanned(ident, "for this type"));
+            return err.to_compile_error().into();
+        }
+    };
 
-    let attr = attrs.remove(0);
-    let namespace = match find_namespace(&attr) {
-        Ok(namespace) => namespace.value(),
+    let (namespace, declared) = match find_namespace(attr) {
+        Ok((namespace, casing)) => (namespace.value(), casing),
         Err(err) => return err.to_compile_error().into(),
     };
 
-    let variants = match build_variant_map(data, attr) {
+    let variant_attr = attrs.iter().find(|attr| attr.path.is_ident("variant"));
+    let variants = match build_variant_map(&ident, data, &attrs, attr, variant_attr) {
         Ok(variants) => variants,
         Err(err) => return err.to_compile_error().into(),
     };
 
-    let matches = variants.clone().into_iter()
-        .map(|(var, fields)| (var, fields.into_iter().filter_map(|v| v.ident)))
-        .map(|(var, fields)| {
-            let args = fields.clone().map(|v| {
+    let strict = attrs.iter().any(|attr| attr.path.is_ident("strict"));
+
+    let separator = match attrs.iter().find(|attr| attr.path.is_ident("separator")) {
+        Some(attr) => match find_separator(attr) {
+            Ok(separator) => Some(separator),
+            Err(err) => return err.to_compile_error().into(),
+        },
+        None => None,
+    };
+
+    let fallback = match attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("template_default"))
+    {
+        Some(attr) => match find_fallback(attr, &variants, declared) {
+            Ok(fallback) => Some(quote! {
+                const FALLBACK: Option<template::Fallback> = Some(#fallback);
+            }),
+            Err(err) => return err.to_compile_error().into(),
+        },
+        None => None,
+    };
+
+    let (delegates, variants): (Vec<_>, Vec<_>) = variants
+        .into_iter()
+        .partition(|(_, _, fields, _)| is_delegate(fields));
+    let delegates = delegates
+        .into_iter()
+        .map(|(var, _, _, _)| var)
+        .collect::<Vec<_>>();
+
+    let matches = variants.iter().map(|(var, _, fields, _)| {
+        let bools = fields
+            .iter()
+            .filter(|v| is_bool(&v.ty))
+            .filter_map(|v| v.ident.as_ref())
+            .collect::<Vec<_>>();
+        let binds = fields
+            .iter()
+            .filter_map(|field| {
+                let v = field.ident.as_ref()?;
                 let k = v.to_string();
-                quote! { with(#k, #v) }
-            });
-            quote! {
-                #ident::#var { #(#fields),* } => {
-                    let args = template::markings::Args::new()#(.#args)*;
-                    let opts = template::markings::Opts::default().optional_keys().duplicate_keys().empty_template().build();
-                    let template = template::markings::Template::parse(template, opts).ok()?;
-                    template.apply(&args).ok()
-                }
-            }
-        });
+                Some(match find_when_true(field) {
+                    Some(text) => quote! {
+                        #k => Some(out.write_str(if *#v { #text } else { "" }))
+                    },
+                    None => quote! {
+                        #k => Some(out.write_fmt(format_args!("{}", #v)))
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        let fields = fields.iter().filter_map(|v| v.ident.as_ref());
 
-    let names_original = variants.iter().map(|(var, _)| {
-        let name = var.to_string();
-        quote! { #ident::#var { .. } => #name }
+        let lookup = if bools.is_empty() {
+            quote! { |_| false }
+        } else {
+            let keys = bools.iter().map(|v| v.to_string());
+            quote! { |key| match key { #(#keys => *#bools,)* _ => false } }
+        };
+        let bind = if binds.is_empty() {
+            quote! { |_, _| None }
+        } else {
+            quote! { |key, out| match key { #(#binds,)* _ => None } }
+        };
+        quote! {
+            #var { #(#fields),* } => template::apply_fields(template, out, #strict, #lookup, #bind)
+        }
+    });
+
+    // an explicit `#[variant("name")]` is used as it is
+    let verbatim = variant_attr.is_some();
+    let variant_name = |casing: Casing, name: &str| {
+        if verbatim {
+            name.to_string()
+        } else {
+            casing.apply(name)
+        }
+    };
+
+    let names = variants.iter().map(|(var, name, _, _)| {
+        let name = cased(|casing| variant_name(casing, name));
+        quote! { #var { .. } => #name }
+    });
+
+    let own_namespaces = variants.iter().map(|(var, _, _, _)| {
+        quote! { #var { .. } => <Self as template::Template>::namespace(casing) }
     });
 
-    let names = variants.iter().map(|(var, _)| {
-        let name = var.to_string().to_snek_case();
-        quote! { #ident::#var { .. } => #name }
+    let own_casings = variants.iter().map(|(var, _, _, _)| {
+        quote! { #var { .. } => <Self as template::Template>::declared_casing() }
     });
 
-    let name_original = ident.to_string();
-    let name = name_original.to_snek_case();
+    let variant_names = cased(|casing| {
+        let names = variants
+            .iter()
+            .map(|(_, name, _, _)| variant_name(casing, name));
+        quote! { &[#(#names),*] }
+    });
 
-    let namespace_original = namespace;
-    let namespace = namespace_original.to_snek_case();
+    let fields = variants.iter().map(|(var, _, fields, _)| {
+        let names = fields
+            .iter()
+            .filter_map(|v| v.ident.as_ref())
+            .map(|v| v.to_string());
+        quote! { #var { .. } => &[#(#names),*] }
+    });
 
+    let name = ident.to_string();
+    let name_cased = cased(|casing| casing.apply(&name));
+    let namespace_cased = cased(|casing| casing.apply(&namespace));
+    let declared_casing = declared.name_casing();
+
+    let key_separator = separator
+        .as_ref()
+        .map_or_else(|| ".".to_string(), syn::LitStr::value);
+    let separator = separator.map(|separator| {
+        quote! {
+            fn separator() -> &'static str {
+                #separator
+            }
+        }
+    });
+
+    let defaults = variants
+        .iter()
+        .filter_map(|(_, name, _, template)| {
+            let (namespace, name) = (declared.apply(&namespace), variant_name(declared, name));
+            let key = format!("{}{}{}", namespace, key_separator, name);
+            template
+                .as_ref()
+                .map(|template| quote! { (#key, #template) })
+        })
+        .collect::<Vec<_>>();
+    let defaults = if defaults.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            const TEMPLATES: &'static [(&'static str, &'static str)] = &[#(#defaults),*];
+        }
+    };
+
+    let delegates = &delegates;
     let ast = quote! {
         impl #generics template::Template for #ident #generics {
             fn namespace(casing: template::NameCasing) -> &'static str {
-                match casing {
-                    template::NameCasing::Snake => { #namespace }
-                    template::NameCasing::Original => { #namespace_original }
-                    _ => unimplemented!()
-                }
+                #namespace_cased
             }
 
             fn name(casing: template::NameCasing) -> &'static str {
-                match casing {
-                    template::NameCasing::Snake => { #name }
-                    template::NameCasing::Original => { #name_original }
-                    _ => unimplemented!()
+                #name_cased
+            }
+
+            fn declared_casing() -> template::NameCasing {
+                #declared_casing
+            }
+
+            fn variant_casing(&self) -> template::NameCasing {
+                match self {
+                    #(#delegates(inner) => template::Template::variant_casing(inner),)*
+                    #(#own_casings),*
+                }
+            }
+
+            #separator
+
+            #defaults
+
+            #fallback
+
+            fn variant(&self, casing: template::NameCasing) -> &'static str {
+                match self {
+                    #(#delegates(inner) => template::Template::variant(inner, casing),)*
+                    #(#names),*
+                }
+            }
+
+            fn variant_namespace(&self, casing: template::NameCasing) -> &'static str {
+                match self {
+                    #(#delegates(inner) => template::Template::variant_namespace(inner, casing),)*
+                    #(#own_namespaces),*
                 }
             }
 
-            fn variant(&self,casing: template::NameCasing) -> &'static str {
-                match casing {
-                    template::NameCasing::Snake => { match self { #(#names),* } }
-                    template::NameCasing::Original => { match self { #(#names_original),* } }
-                    _ => unimplemented!()
+            fn variants(casing: template::NameCasing) -> &'static [&'static str] {
+                #variant_names
+            }
+
+            fn fields(&self) -> &'static [&'static str] {
+                match self {
+                    #(#delegates(inner) => template::Template::fields(inner),)*
+                    #(#fields),*
                 }
             }
 
             fn apply(&self, template: &str) -> Option<String> {
-                match self { #(#matches),* }
+                let mut output = String::with_capacity(template.len());
+                match template::Template::apply_fmt(self, template, &mut output) {
+                    Ok(true) => Some(output),
+                    _ => None,
+                }
+            }
+
+            fn apply_fmt(&self, template: &str, out: &mut dyn std::fmt::Write) -> Result<bool, std::fmt::Error> {
+                // templates without any `${..}` are used as they are
+                if !template.contains("${") {
+                    return out.write_str(template).map(|_| true);
+                }
+                match self {
+                    #(#delegates(inner) => template::Template::apply_fmt(inner, template, out),)*
+                    #(#matches),*
+                }
             }
         }
     };
     ast.into()
 }
 
-fn find_namespace(attr: &syn::Attribute) -> Result<syn::LitStr, syn::Error> {
-    let ns = match attr.parse_args::<syn::Lit>() {
-        Ok(syn::Lit::Str(namespace)) => namespace,
-        Ok(attr) => {
+fn is_delegate(fields: &[syn::Field]) -> bool {
+    matches!(fields, [field] if field.ident.is_none())
+}
+
+fn is_bool(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(path) => path.qself.is_none() && path.path.is_ident("bool"),
+        _ => false,
+    }
+}
+
+fn find_namespace(attr: &syn::Attribute) -> Result<(syn::LitStr, Casing), syn::Error> {
+    let args = attr
+        .parse_args_with(
+            syn::punctuated::Punctuated::<syn::NestedMeta, syn::Token![,]>::parse_terminated,
+        )
+        // TODO say we cannot parse the name into a Lit (when can this happen?)
+        .map_err(|err| Error::new(attr.span(), err))?;
+
+    let mut args = args.into_iter();
+    let ns = match args.next() {
+        Some(syn::NestedMeta::Lit(syn::Lit::Str(namespace))) => namespace,
+        Some(arg) => {
+            return Err(Error::new(
+                arg.span(), //
+                "A string literal must be used as a `namespace` identifier.",
+            ));
+        }
+        None => {
             return Err(Error::new(
                 attr.span(), //
                 "A string literal must be used as a `namespace` identifier.",
             ));
         }
-        // TODO say we cannot parse the name into a Lit (when can this happen?)
-        Err(err) => return Err(Error::new(attr.span(), err)),
     };
 
+    let casing = match args.next() {
+        Some(syn::NestedMeta::Meta(syn::Meta::NameValue(arg))) if arg.path.is_ident("casing") => {
+            find_casing(&arg.lit)?
+        }
+        Some(arg) => {
+            return Err(Error::new(
+                arg.span(), //
+                "Only `casing = \"...\"` is allowed after the namespace.",
+            ));
+        }
+        None => Casing::Snake,
+    };
+
+    if let Some(arg) = args.next() {
+        return Err(Error::new(
+            arg.span(), //
+            "Only `casing = \"...\"` is allowed after the namespace.",
+        ));
+    }
+
     let namespace = ns.value();
 
     if namespace.chars().take_while(|c| !c.is_alphabetic()).count() > 0 {
@@ -146,13 +409,31 @@ fn find_namespace(attr: &syn::Attribute) -> Result<syn::LitStr, syn::Error> {
         ));
     }
 
-    Ok(ns)
+    Ok((ns, casing))
+}
+
+fn find_casing(lit: &syn::Lit) -> Result<Casing, syn::Error> {
+    match lit {
+        syn::Lit::Str(casing) if casing.value() == "snake" => Ok(Casing::Snake),
+        syn::Lit::Str(casing) if casing.value() == "kebab" => Ok(Casing::Kebab),
+        syn::Lit::Str(casing) if casing.value() == "original" => Ok(Casing::Original),
+        lit => Err(Error::new(
+            lit.span(),
+            "The `casing` must be one of \"snake\", \"kebab\" or \"original\".",
+        )),
+    }
 }
 
+// (the path, the name, the fields, the default template)
+type Variant = (syn::Path, String, Vec<syn::Field>, Option<syn::LitStr>);
+
 fn build_variant_map(
+    ident: &syn::Ident,
     data: syn::Data,
-    attr: syn::Attribute,
-) -> Result<Vec<(syn::Ident, Vec<syn::Field>)>, syn::Error> {
+    attrs: &[syn::Attribute],
+    attr: &syn::Attribute,
+    variant_attr: Option<&syn::Attribute>,
+) -> Result<Vec<Variant>, syn::Error> {
     let variants = match data {
         syn::Data::Enum(e) if !e.variants.is_empty() => e.variants,
         syn::Data::Enum(e) => {
@@ -161,35 +442,218 @@ fn build_variant_map(
                 "Atleast one variant must be supplied",
             ))
         }
-        _ => return Err(Error::new(attr.span(), "Only enums are allowed.")),
+        syn::Data::Struct(s) => {
+            let name = match variant_attr {
+                Some(attr) => find_variant(attr)?.value(),
+                None => ident.to_string(),
+            };
+            let fields = build_fields(s.fields)?;
+            let template = find_template(attrs)?;
+            if let (Some(template), true) = (&template, fields.is_empty()) {
+                check_unit_template(template)?;
+            }
+            return Ok(vec![(ident.clone().into(), name, fields, template)]);
+        }
+        _ => {
+            return Err(Error::new(
+                attr.span(),
+                "Only enums and structs are allowed.",
+            ))
+        }
     };
 
+    if let Some(attr) = variant_attr {
+        return Err(Error::new(
+            attr.span(),
+            "The `variant` attribute is only allowed on structs.",
+        ));
+    }
+
     let mut results = vec![];
     for variant in variants {
-        let ident = variant.ident;
-        let fields = match variant.fields {
-            syn::Fields::Named(fields) => fields,
-            syn::Fields::Unit => {
-                results.push((ident, vec![]));
-                continue;
-            }
-            field => {
-                return Err(Error::new(
-                    field.span(), //
-                    "Only named fields are allowed.",
-                ));
-            }
-        };
+        let var = variant.ident;
+        let name = var.to_string();
+        let fields = build_fields(variant.fields)?;
+        let template = find_template(&variant.attrs)?;
+        if template.is_some() && is_delegate(&fields) {
+            return Err(Error::new(
+                var.span(),
+                "The `template` attribute is not allowed on a variant wrapping another template.",
+            ));
+        }
+        if let (Some(template), true) = (&template, fields.is_empty()) {
+            check_unit_template(template)?;
+        }
+        results.push((syn::parse_quote! { #ident::#var }, name, fields, template));
+    }
+
+    Ok(results)
+}
+
+fn build_fields(fields: syn::Fields) -> Result<Vec<syn::Field>, syn::Error> {
+    let fields = match fields {
+        syn::Fields::Named(fields) => fields,
+        syn::Fields::Unit => return Ok(vec![]),
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            return Ok(fields.unnamed.into_iter().collect())
+        }
+        field => {
+            return Err(Error::new(
+                field.span(), //
+                "Only named fields, or a single unnamed field, are allowed.",
+            ));
+        }
+    };
+
+    if fields.named.is_empty() {
+        return Err(Error::new(
+            fields.named.span(), //
+            "Named variants must have fields.",
+        ));
+    }
 
-        if fields.named.is_empty() {
+    for ident in fields.named.iter().filter_map(|field| field.ident.as_ref()) {
+        let name = ident.unraw().to_string();
+        if RESERVED.contains(&name.as_str()) {
             return Err(Error::new(
-                fields.named.span(), //
-                "Named variants must have fields.",
+                ident.span(),
+                format!("`{}` is reserved and cannot be used as a field name.", name),
             ));
         }
+    }
 
-        results.push((ident, fields.named.into_iter().collect()));
+    for field in &fields.named {
+        let attr = match field
+            .attrs
+            .iter()
+            .find(|attr| attr.path.is_ident("when_true"))
+        {
+            Some(attr) => attr,
+            None => continue,
+        };
+        if !is_bool(&field.ty) {
+            return Err(Error::new(
+                field.ty.span(), //
+                "The `when_true` attribute is only allowed on `bool` fields.",
+            ));
+        }
+        match attr.parse_args::<syn::Lit>() {
+            Ok(syn::Lit::Str(..)) => {}
+            Ok(attr) => {
+                return Err(Error::new(
+                    attr.span(), //
+                    "A string literal must be used as the `when_true` text.",
+                ));
+            }
+            Err(err) => return Err(Error::new(attr.span(), err)),
+        }
     }
 
-    Ok(results)
+    Ok(fields.named.into_iter().collect())
+}
+
+// this is validated by `build_fields`
+fn find_when_true(field: &syn::Field) -> Option<syn::LitStr> {
+    let attr = field
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("when_true"))?;
+    match attr.parse_args::<syn::Lit>() {
+        Ok(syn::Lit::Str(text)) => Some(text),
+        _ => None,
+    }
+}
+
+fn find_separator(attr: &syn::Attribute) -> Result<syn::LitStr, syn::Error> {
+    match attr.parse_args::<syn::Lit>() {
+        Ok(syn::Lit::Str(separator)) if !separator.value().is_empty() => Ok(separator),
+        Ok(attr) => Err(Error::new(
+            attr.span(), //
+            "A non-empty string literal must be used as a `separator`.",
+        )),
+        Err(err) => Err(Error::new(attr.span(), err)),
+    }
+}
+
+fn find_template(attrs: &[syn::Attribute]) -> Result<Option<syn::LitStr>, syn::Error> {
+    let attr = match attrs.iter().find(|attr| attr.path.is_ident("template")) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+    match attr.parse_args::<syn::Lit>() {
+        Ok(syn::Lit::Str(template)) => Ok(Some(template)),
+        Ok(attr) => Err(Error::new(
+            attr.span(), //
+            "A string literal must be used as a `template`.",
+        )),
+        Err(err) => Err(Error::new(attr.span(), err)),
+    }
+}
+
+fn find_fallback(
+    attr: &syn::Attribute,
+    variants: &[Variant],
+    declared: Casing,
+) -> Result<syn::Expr, syn::Error> {
+    match attr.parse_args::<syn::NestedMeta>() {
+        Ok(syn::NestedMeta::Lit(syn::Lit::Str(template))) => {
+            Ok(syn::parse_quote! { template::Fallback::Template(#template) })
+        }
+        Ok(syn::NestedMeta::Meta(syn::Meta::Path(path))) => {
+            // only enum variants have a `Type::Variant` path
+            let (_, name, fields, _) = variants
+                .iter()
+                .filter(|(var, _, _, _)| var.segments.len() == 2)
+                .find(|(var, _, _, _)| {
+                    let ident = var.segments.last().map(|segment| &segment.ident);
+                    path.get_ident().is_some() && ident == path.get_ident()
+                })
+                .ok_or_else(|| {
+                    Error::new(
+                        path.span(), //
+                        "The `template_default` variant must be a variant of this enum.",
+                    )
+                })?;
+            if is_delegate(fields) {
+                return Err(Error::new(
+                    path.span(), //
+                    "The `template_default` variant cannot wrap another template.",
+                ));
+            }
+            let name = declared.apply(name);
+            Ok(syn::parse_quote! { template::Fallback::Variant(#name) })
+        }
+        Ok(arg) => Err(Error::new(
+            arg.span(), //
+            "A variant or a string literal must be used as the `template_default`.",
+        )),
+        Err(err) => Err(Error::new(attr.span(), err)),
+    }
+}
+
+// a variant without fields can't bind any of the keys in its template
+fn check_unit_template(template: &syn::LitStr) -> Result<(), syn::Error> {
+    let value = template.value();
+    let key = match value.split_once("${") {
+        Some((_, rest)) => rest.split('}').next().unwrap_or(rest).trim(),
+        None => return Ok(()),
+    };
+    Err(Error::new(
+        template.span(),
+        format!(
+            "A variant without fields cannot use `${{{}}}` in its `template`.",
+            key
+        ),
+    ))
+}
+
+fn find_variant(attr: &syn::Attribute) -> Result<syn::LitStr, syn::Error> {
+    match attr.parse_args::<syn::Lit>() {
+        Ok(syn::Lit::Str(variant)) if !variant.value().trim().is_empty() => Ok(variant),
+        Ok(attr) => Err(Error::new(
+            attr.span(), //
+            "A non-empty string literal must be used as a `variant` name.",
+        )),
+        Err(err) => Err(Error::new(attr.span(), err)),
+    }
 }
@@ -0,0 +1,294 @@
+use template::{Fallback, MemoryStore, NameCasing, Resolver, Template};
+
+#[derive(Template, Debug)]
+#[namespace("response")]
+enum Response<'a> {
+    Hello {
+        name: &'a str,
+    },
+    CountItems {
+        count: usize,
+    },
+    Okay,
+    Status {
+        premium: bool,
+    },
+    Verified {
+        name: &'a str,
+        #[when_true(" (verified)")]
+        verified: bool,
+    },
+    Error(ErrorResponse),
+}
+
+#[derive(Template, Debug)]
+#[namespace("error")]
+enum ErrorResponse {
+    NotFound { id: usize },
+}
+
+#[derive(Template, Debug)]
+#[namespace("user")]
+struct UserCreated {
+    name: String,
+}
+
+#[derive(Template, Debug)]
+#[namespace("user")]
+#[variant("created_at")]
+struct Renamed {
+    at: u64,
+}
+
+#[derive(Template, Debug)]
+#[namespace("strict")]
+#[strict]
+enum Strict {
+    Hello { name: &'static str },
+}
+
+#[derive(Template, Debug)]
+#[namespace("HttpResponse", casing = "kebab")]
+#[separator("/")]
+enum Kebab {
+    NotFound,
+    Wrapped(ErrorResponse),
+}
+
+#[derive(Template, Debug)]
+#[namespace("Kept", casing = "kebab")]
+#[variant("the_struct")]
+struct Verbatim {
+    id: u8,
+}
+
+#[derive(Template, Debug)]
+#[namespace("defaults")]
+#[template_default(Unknown)]
+enum Defaults {
+    #[template("hello ${name}")]
+    Hello {
+        name: &'static str,
+    },
+    #[template("okay")]
+    Okay,
+    Missing,
+    #[template("unknown")]
+    Unknown,
+}
+
+#[derive(Template, Debug)]
+#[namespace("literal")]
+#[template_default("fallback ${id}")]
+enum LiteralDefault {
+    Missing { id: u8 },
+}
+
+fn resolver(input: &str) -> Resolver<MemoryStore> {
+    Resolver::new(MemoryStore::new(input.to_string(), template::load_lines)).unwrap()
+}
+
+#[test]
+fn names() {
+    assert_eq!(Response::namespace(NameCasing::Snake), "response");
+    assert_eq!(Response::name(NameCasing::Snake), "response");
+    assert_eq!(Response::Okay.variant(NameCasing::Snake), "okay");
+    assert_eq!(
+        Response::CountItems { count: 1 }.variant(NameCasing::Snake),
+        "count_items"
+    );
+    assert_eq!(
+        Response::CountItems { count: 1 }.variant(NameCasing::Original),
+        "CountItems"
+    );
+    assert_eq!(
+        Response::CountItems { count: 1 }.variant(NameCasing::Kebab),
+        "count-items"
+    );
+    assert_eq!(
+        Response::variants(NameCasing::Snake),
+        &["hello", "count_items", "okay", "status", "verified"]
+    );
+    assert_eq!(Response::Hello { name: "" }.fields(), &["name"]);
+    assert_eq!(Response::Okay.fields(), &[] as &[&str]);
+    assert_eq!(
+        Response::Okay.key(NameCasing::Snake),
+        "response.okay".to_string()
+    );
+}
+
+#[test]
+fn apply() {
+    let hello = Response::Hello { name: "world" };
+    assert_eq!(hello.apply("hello ${name}!").unwrap(), "hello world!");
+    assert_eq!(hello.apply("hello ${unknown}").unwrap(), "hello ${unknown}");
+    assert_eq!(hello.apply("hello ${name").as_deref(), None);
+    assert_eq!(hello.apply("hello ${na{me}").as_deref(), None);
+
+    let count = Response::CountItems { count: 42 };
+    assert_eq!(count.apply("count is: ${count}").unwrap(), "count is: 42");
+    assert_eq!(
+        Response::Okay.apply("okay response").unwrap(),
+        "okay response"
+    );
+
+    let mut out = String::new();
+    assert!(hello.apply_fmt("hello ${name}", &mut out).unwrap());
+    assert_eq!(out, "hello world");
+}
+
+#[test]
+fn conditionals() {
+    let input = "${if premium}thanks!${else}upgrade?${endif}";
+    let premium = Response::Status { premium: true };
+    assert_eq!(premium.apply(input).unwrap(), "thanks!");
+    let free = Response::Status { premium: false };
+    assert_eq!(free.apply(input).unwrap(), "upgrade?");
+
+    let nested = "${if premium}a${if unknown}b${else}c${endif}${endif}";
+    assert_eq!(premium.apply(nested).unwrap(), "ac");
+    assert_eq!(premium.apply("${if premium}unterminated"), None);
+    assert_eq!(premium.apply("${else}"), None);
+}
+
+#[test]
+fn when_true() {
+    let input = "${name}${verified}";
+    let verified = Response::Verified {
+        name: "bob",
+        verified: true,
+    };
+    assert_eq!(verified.apply(input).unwrap(), "bob (verified)");
+    let unverified = Response::Verified {
+        name: "bob",
+        verified: false,
+    };
+    assert_eq!(unverified.apply(input).unwrap(), "bob");
+}
+
+#[test]
+fn delegates() {
+    let error = Response::Error(ErrorResponse::NotFound { id: 42 });
+    assert_eq!(error.variant_namespace(NameCasing::Snake), "error");
+    assert_eq!(error.variant(NameCasing::Snake), "not_found");
+    assert_eq!(error.fields(), &["id"]);
+    assert_eq!(error.key(NameCasing::Snake), "error.not_found");
+    assert_eq!(
+        error.apply("${id} was not found").unwrap(),
+        "42 was not found"
+    );
+
+    let mut resolver = resolver("error.not_found = ${id} was not found");
+    assert_eq!(resolver.apply(&error).unwrap().unwrap(), "42 was not found");
+}
+
+#[test]
+fn structs() {
+    let created = UserCreated {
+        name: "alice".into(),
+    };
+    assert_eq!(created.variant(NameCasing::Snake), "user_created");
+    assert_eq!(UserCreated::variants(NameCasing::Snake), &["user_created"]);
+    assert_eq!(created.apply("hi ${name}").unwrap(), "hi alice");
+
+    let renamed = Renamed { at: 1 };
+    assert_eq!(renamed.variant(NameCasing::Snake), "created_at");
+    assert_eq!(renamed.apply("at ${at}").unwrap(), "at 1");
+}
+
+#[test]
+fn strict() {
+    let hello = Strict::Hello { name: "world" };
+    assert_eq!(hello.apply("hello ${name}").unwrap(), "hello world");
+    assert_eq!(hello.apply("hello ${typo}"), None);
+    assert_eq!(hello.apply("${if typo}${endif}hello").unwrap(), "hello");
+}
+
+#[test]
+fn casing() {
+    assert_eq!(Kebab::declared_casing(), NameCasing::Kebab);
+    assert_eq!(Kebab::namespace(NameCasing::Kebab), "http-response");
+    assert_eq!(Kebab::namespace(NameCasing::Snake), "http_response");
+    assert_eq!(Kebab::NotFound.variant(NameCasing::Kebab), "not-found");
+    assert_eq!(Kebab::NotFound.variant_casing(), NameCasing::Kebab);
+
+    let wrapped = Kebab::Wrapped(ErrorResponse::NotFound { id: 1 });
+    assert_eq!(wrapped.variant_casing(), NameCasing::Snake);
+
+    // an explicit variant name is used as it is
+    assert_eq!(Verbatim::declared_casing(), NameCasing::Kebab);
+    assert_eq!(Verbatim { id: 1 }.variant(NameCasing::Kebab), "the_struct");
+    assert_eq!(Verbatim { id: 1 }.variant(NameCasing::Snake), "the_struct");
+
+    let mut resolver = resolver(
+        "http-response.not-found = not found\nerror.not_found = error ${id}\nkept.the_struct = kept ${id}",
+    );
+    assert_eq!(
+        resolver.apply(&Kebab::NotFound).unwrap().unwrap(),
+        "not found"
+    );
+    assert_eq!(resolver.apply(&wrapped).unwrap().unwrap(), "error 1");
+    assert_eq!(
+        resolver.apply(&Verbatim { id: 2 }).unwrap().unwrap(),
+        "kept 2"
+    );
+}
+
+#[test]
+fn separator() {
+    assert_eq!(Kebab::separator(), "/");
+    assert_eq!(
+        Kebab::NotFound.key(NameCasing::Kebab),
+        "http-response/not-found"
+    );
+    assert_eq!(Response::separator(), ".");
+}
+
+#[test]
+fn templates() {
+    assert_eq!(
+        Defaults::TEMPLATES,
+        &[
+            ("defaults.hello", "hello ${name}"),
+            ("defaults.okay", "okay"),
+            ("defaults.unknown", "unknown"),
+        ]
+    );
+    assert_eq!(Response::TEMPLATES, &[]);
+
+    let input = Defaults::TEMPLATES
+        .iter()
+        .map(|(key, template)| format!("{} = {}\n", key, template))
+        .collect::<String>();
+    let mut resolver = resolver(&input);
+    let hello = Defaults::Hello { name: "world" };
+    assert_eq!(resolver.apply(&hello).unwrap().unwrap(), "hello world");
+    assert_eq!(resolver.apply(&Defaults::Okay).unwrap().unwrap(), "okay");
+    assert_eq!(
+        resolver.apply(&Defaults::Unknown).unwrap().unwrap(),
+        "unknown"
+    );
+}
+
+#[test]
+fn template_default() {
+    assert_eq!(Defaults::FALLBACK, Some(Fallback::Variant("unknown")));
+    assert_eq!(
+        LiteralDefault::FALLBACK,
+        Some(Fallback::Template("fallback ${id}"))
+    );
+    assert_eq!(Response::FALLBACK, None);
+
+    let mut resolver = resolver("defaults.unknown = stored unknown");
+    assert_eq!(
+        resolver.apply(&Defaults::Missing).unwrap().unwrap(),
+        "stored unknown"
+    );
+    assert_eq!(
+        resolver
+            .apply(&LiteralDefault::Missing { id: 7 })
+            .unwrap()
+            .unwrap(),
+        "fallback 7"
+    );
+}
@@ -0,0 +1,202 @@
+use template::{Error, MergedStore, TemplateMap, TemplateStore, Templates};
+
+// a store that counts how often it was parsed
+#[derive(Debug)]
+struct Counted {
+    data: &'static str,
+    changed: bool,
+    parsed: usize,
+}
+
+impl Counted {
+    fn new(data: &'static str) -> Self {
+        Self {
+            data,
+            changed: false,
+            parsed: 0,
+        }
+    }
+
+    fn update(&mut self, data: &'static str) {
+        self.data = data;
+        self.changed = true;
+    }
+}
+
+impl TemplateStore for Counted {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.changed = false;
+        self.parsed += 1;
+        template::load_lines(self.data)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.changed
+    }
+}
+
+#[test]
+fn merged_store_refreshes_changed_namespaces() {
+    let store = MergedStore::new(vec![
+        Counted::new("a.x = 1\nb.y = 2"),
+        Counted::new("c.z = 3"),
+    ]);
+    let mut templates = Templates::new(store).unwrap();
+    let version = templates.version();
+
+    // `b` is removed, `a` is changed
+    templates.store_mut().stores_mut()[0].update("a.x = 10");
+    let changes = templates.refresh_changes().unwrap();
+    assert_eq!(changes, vec!["a.x", "b.y"]);
+    assert_eq!(templates.version(), version + 1);
+
+    assert_eq!(templates.get("a").unwrap().get("x").unwrap(), "10");
+    assert!(templates.get("b").is_none());
+    assert_eq!(templates.get("c").unwrap().get("z").unwrap(), "3");
+
+    // the unchanged store wasn't parsed again
+    let parsed = templates
+        .store()
+        .stores()
+        .iter()
+        .map(|store| store.parsed)
+        .collect::<Vec<_>>();
+    assert_eq!(parsed, vec![2, 1]);
+}
+
+#[test]
+fn merged_store_keeps_the_later_stores_namespaces() {
+    let store = MergedStore::new(vec![Counted::new("a.x = 1"), Counted::new("a.x = 2")]);
+    let mut templates = Templates::new(store).unwrap();
+
+    // the namespace is replaced by the later store, so nothing changes
+    templates.store_mut().stores_mut()[0].update("a.x = 10\nb.y = 20");
+    let changes = templates.refresh_changes().unwrap();
+    assert_eq!(changes, vec!["b.y"]);
+    assert_eq!(templates.get("a").unwrap().get("x").unwrap(), "2");
+    assert_eq!(templates.get("b").unwrap().get("y").unwrap(), "20");
+}
+
+// a store that can tell which namespaces changed
+#[derive(Debug, Default)]
+struct Keyed {
+    data: &'static str,
+    changed: Option<Vec<String>>,
+    full: usize,
+}
+
+impl Keyed {
+    fn update(&mut self, data: &'static str, namespaces: &[&str]) {
+        self.data = data;
+        self.changed = Some(namespaces.iter().map(|s| s.to_string()).collect());
+    }
+}
+
+impl TemplateStore for Keyed {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        self.full += 1;
+        self.changed.take();
+        template::load_lines(self.data)
+    }
+
+    fn changed(&mut self) -> bool {
+        self.changed.is_some()
+    }
+
+    fn changed_keys(&mut self) -> Option<Vec<String>> {
+        self.changed.clone()
+    }
+
+    fn parse_keys(&mut self, keys: &[String]) -> Result<TemplateMap<String>, Error> {
+        self.changed.take();
+        let mut map = template::load_lines(self.data)?;
+        map.retain(|key, _| keys.contains(key));
+        Ok(map)
+    }
+}
+
+fn check_forwarded<S: TemplateStore>(store: S, keyed: impl Fn(&mut S) -> &mut Keyed) {
+    let mut templates = Templates::new(store).unwrap();
+    keyed(templates.store_mut()).update("a.x = 10\nc.z = 3", &["a", "b"]);
+    let changes = templates.refresh_changes().unwrap();
+    // `c` wasn't reported as changed, so it isn't picked up
+    assert_eq!(changes, vec!["a.x", "b.y"]);
+    assert!(templates.get("c").is_none());
+    assert_eq!(keyed(templates.store_mut()).full, 1);
+}
+
+#[test]
+fn wrappers_forward_changed_keys() {
+    use template::{LimitedStore, MapKeysStore, PartialStore, RetryStore, TrimStore};
+    let keyed = || Keyed {
+        data: "a.x = 1\nb.y = 2",
+        ..Keyed::default()
+    };
+
+    check_forwarded(MergedStore::new(vec![keyed()]), |s| &mut s.stores_mut()[0]);
+    check_forwarded(PartialStore::new(Counted::new("d.w = 0"), keyed()), |s| {
+        s.partial_mut()
+    });
+    check_forwarded(LimitedStore::new(keyed(), 10), |s| s.inner_mut());
+    check_forwarded(MapKeysStore::new(keyed(), str::to_lowercase), |s| {
+        s.inner_mut()
+    });
+    check_forwarded(
+        RetryStore::new(keyed(), 0, std::time::Duration::ZERO),
+        |s| s.inner_mut(),
+    );
+    check_forwarded(TrimStore::new(keyed()), |s| s.inner_mut());
+    check_forwarded(Some(keyed()), |s| s.as_mut().unwrap());
+}
+
+// a store that loads something else the first time
+#[derive(Debug)]
+struct Initial(&'static str);
+
+impl TemplateStore for Initial {
+    fn parse_map(&mut self) -> Result<TemplateMap<String>, Error> {
+        template::load_lines(self.0)
+    }
+
+    fn initial_load(&mut self) -> Result<TemplateMap<String>, Error> {
+        template::load_lines("i.x = initial")
+    }
+
+    fn changed(&mut self) -> bool {
+        false
+    }
+}
+
+fn check_initial<S: TemplateStore>(store: S) {
+    let templates = Templates::new(store).unwrap();
+    assert_eq!(templates.get("i").unwrap().get("x").unwrap(), "initial");
+}
+
+#[test]
+fn wrappers_forward_initial_load() {
+    use template::{LimitedStore, MapKeysStore, PartialStore, RetryStore, TrimStore};
+
+    check_initial(MergedStore::new(vec![
+        Initial("a.x = 1"),
+        Initial("b.x = 1"),
+    ]));
+    check_initial(PartialStore::new(
+        Initial("a.x = 1"),
+        Counted::new("b.x = 1"),
+    ));
+    check_initial(PartialStore::new(
+        Counted::new("a.x = 1"),
+        Initial("b.x = 1"),
+    ));
+    check_initial(LimitedStore::new(Initial("a.x = 1"), 10));
+    check_initial(MapKeysStore::new(Initial("a.x = 1"), str::to_lowercase));
+    check_initial(RetryStore::new(
+        Initial("a.x = 1"),
+        0,
+        std::time::Duration::ZERO,
+    ));
+    #[cfg(feature = "env-interp")]
+    check_initial(template::EnvStore::new(Initial("a.x = 1")));
+    check_initial(TrimStore::new(Initial("a.x = 1")));
+    check_initial(Some(Initial("a.x = 1")));
+}
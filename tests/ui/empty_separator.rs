@@ -0,0 +1,8 @@
+#[derive(template::Template)]
+#[namespace("response")]
+#[separator("")]
+enum Response {
+    Okay,
+}
+
+fn main() {}
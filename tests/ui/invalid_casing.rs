@@ -0,0 +1,7 @@
+#[derive(template::Template)]
+#[namespace("response", casing = "shouting")]
+enum Response {
+    Okay,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[derive(template::Template)]
+enum Response {
+    Okay,
+}
+
+fn main() {}
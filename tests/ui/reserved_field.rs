@@ -0,0 +1,7 @@
+#[derive(template::Template)]
+#[namespace("response")]
+enum Response {
+    Hello { endif: bool },
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+#[derive(template::Template)]
+#[namespace("error")]
+enum Error {
+    NotFound,
+}
+
+#[derive(template::Template)]
+#[namespace("response")]
+#[template_default(Error)]
+enum Response {
+    Okay,
+    Error(Error),
+}
+
+fn main() {}
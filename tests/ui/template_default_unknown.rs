@@ -0,0 +1,8 @@
+#[derive(template::Template)]
+#[namespace("response")]
+#[template_default(Missing)]
+enum Response {
+    Okay,
+}
+
+fn main() {}
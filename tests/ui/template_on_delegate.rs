@@ -0,0 +1,14 @@
+#[derive(template::Template)]
+#[namespace("error")]
+enum Error {
+    NotFound,
+}
+
+#[derive(template::Template)]
+#[namespace("response")]
+enum Response {
+    #[template("wrapped")]
+    Error(Error),
+}
+
+fn main() {}
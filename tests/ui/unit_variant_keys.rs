@@ -0,0 +1,8 @@
+#[derive(template::Template)]
+#[namespace("response")]
+enum Response {
+    #[template("okay ${name}")]
+    Okay,
+}
+
+fn main() {}
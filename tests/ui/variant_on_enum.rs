@@ -0,0 +1,8 @@
+#[derive(template::Template)]
+#[namespace("response")]
+#[variant("okay")]
+enum Response {
+    Okay,
+}
+
+fn main() {}
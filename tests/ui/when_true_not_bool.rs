@@ -0,0 +1,10 @@
+#[derive(template::Template)]
+#[namespace("response")]
+enum Response {
+    Hello {
+        #[when_true("yes")]
+        name: String,
+    },
+}
+
+fn main() {}